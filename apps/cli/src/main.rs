@@ -1,5 +1,10 @@
 use clap::Parser;
-use ntscloom_core::{process_frame, DemodulationFilter, Frame, PipelineConfig};
+use std::path::PathBuf;
+
+use ntscloom_core::{
+    AxisProfile, CaptionTrack, ClampMode, DemodulationFilter, Frame, PhosphorMask, Pipeline,
+    PipelineConfig,
+};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about = "NTSCloom CLI batch renderer prototype")]
@@ -10,20 +15,91 @@ struct Args {
     height: usize,
     #[arg(long, default_value = "consumer-vhs")]
     preset: String,
-    #[arg(long, default_value = "lowpass")]
-    demod: String,
+    /// Decoder: lowpass, box, notch, comb1d, comb2d, adaptive, comb3d (default: preset).
+    #[arg(long)]
+    demod: Option<String>,
+    /// Decoder axes: iq, equiband, consumer-mild, red-push, flesh-tone (default: preset).
+    #[arg(long)]
+    axes: Option<String>,
     #[arg(long, default_value_t = 2)]
     oversample: u8,
+    #[arg(long, default_value_t = 1)]
+    frames: u32,
+    /// SCC or SRT file to send as line 21 captions; prints how many bytes survive.
+    #[arg(long)]
+    captions: Option<PathBuf>,
+    /// Insert VITS on lines 17-20 and print the measurements from the last frame.
+    #[arg(long)]
+    vits: bool,
+    /// Show the result on a CRT with this mask: grille, slot, shadow or none.
+    #[arg(long)]
+    crt: Option<String>,
 }
 
 fn main() {
     let args = Args::parse();
     let frame = Frame::new(args.width, args.height);
     let mut config = preset_config(&args.preset);
-    config.demodulation.filter = parse_demod(&args.demod);
+    if let Some(demod) = &args.demod {
+        config.demodulation.filter = parse_demod(demod);
+    }
+    if let Some(axes) = &args.axes {
+        config.demodulation.axes = parse_axes(axes).axes();
+    }
     config.precision.oversample_factor = args.oversample;
-    let _out = process_frame(&frame, &config, 14_318_180.0);
-    println!("Rendered {}x{} frame through NTSCloom pipeline.", args.width, args.height);
+    config.vits.enabled |= args.vits;
+    if let Some(mask) = &args.crt {
+        config.crt.enabled = true;
+        config.crt.mask = parse_mask(mask);
+    }
+    let mut pipeline = Pipeline::new(config, 14_318_180.0);
+    if let Some(path) = &args.captions {
+        match CaptionTrack::load(path) {
+            Ok(track) => pipeline.set_captions(Some(track)),
+            Err(err) => {
+                eprintln!("Could not load captions: {err}");
+                std::process::exit(1);
+            }
+        }
+    }
+    for conflict in pipeline.vbi_conflicts() {
+        eprintln!("Warning: {conflict}");
+    }
+    for _ in 0..args.frames {
+        let _out = pipeline.process(&frame);
+    }
+    println!(
+        "Rendered {} {}x{} frame(s) through NTSCloom pipeline.",
+        args.frames, args.width, args.height
+    );
+    if args.captions.is_some() {
+        let report = pipeline.caption_report();
+        println!(
+            "Captions: {}/{} bytes intact ({:.1}%), {} parity errors, {} undetected errors, \
+             {} lines lost.",
+            report.bytes_intact,
+            report.bytes_sent,
+            report.survival() * 100.0,
+            report.parity_errors,
+            report.undetected_errors,
+            report.lines_lost
+        );
+    }
+    if args.vits {
+        let vits = pipeline.vits();
+        for (mhz, db) in &vits.frequency_response {
+            println!("VITS multiburst {mhz:.2} MHz: {db:+.2} dB");
+        }
+        let reading = |value: Option<f32>| value.map_or("-".to_string(), |v| format!("{v:.2}"));
+        println!(
+            "VITS DG {}%, DP {} deg, K2T {}%, bar tilt {}%, chroma/luma gain {}%",
+            reading(vits.differential_gain_pct),
+            reading(vits.differential_phase_deg),
+            reading(vits.k_pulse_bar_pct),
+            reading(vits.bar_tilt_pct),
+            reading(vits.chroma_luma_gain_pct)
+        );
+    }
 }
 
 fn parse_demod(value: &str) -> DemodulationFilter {
@@ -32,10 +108,31 @@ fn parse_demod(value: &str) -> DemodulationFilter {
         "notch" => DemodulationFilter::Notch,
         "comb1d" => DemodulationFilter::Comb1D,
         "comb2d" => DemodulationFilter::Comb2D,
+        "adaptive" | "adaptive-comb" => DemodulationFilter::AdaptiveComb,
+        "comb3d" => DemodulationFilter::Comb3D,
         _ => DemodulationFilter::Lowpass,
     }
 }
 
+fn parse_axes(value: &str) -> AxisProfile {
+    match value.to_lowercase().as_str() {
+        "equiband" => AxisProfile::EquibandColorDifference,
+        "consumer-mild" => AxisProfile::ConsumerMild,
+        "red-push" => AxisProfile::ConsumerRedPush,
+        "flesh-tone" => AxisProfile::ConsumerFleshTone,
+        _ => AxisProfile::TextbookIq,
+    }
+}
+
+fn parse_mask(value: &str) -> PhosphorMask {
+    match value.to_lowercase().as_str() {
+        "slot" | "slot-mask" => PhosphorMask::SlotMask,
+        "shadow" | "shadow-mask" => PhosphorMask::ShadowMask,
+        "none" => PhosphorMask::None,
+        _ => PhosphorMask::ApertureGrille,
+    }
+}
+
 fn preset_config(name: &str) -> PipelineConfig {
     let mut config = PipelineConfig::default();
     match name.to_lowercase().as_str() {
@@ -44,30 +141,53 @@ fn preset_config(name: &str) -> PipelineConfig {
             config.tape.flutter_depth = 0.02;
             config.artifacts.head_switch_intensity = 0.1;
             config.artifacts.dropout_rate = 0.0;
-            config.demodulation.filter = DemodulationFilter::Comb2D;
+            config.demodulation.filter = DemodulationFilter::Comb3D;
         }
         "damaged-tape" => {
             config.tape.dropout_rate = 0.08;
             config.artifacts.dropout_rate = 0.08;
             config.artifacts.head_switch_intensity = 0.6;
             config.artifacts.saturation_strength = 0.5;
+            config.channel.chroma_noise = 0.06;
+            config.channel.color_noise_texture_scale = 3.0;
+            config.tape.tape_hiss_db = -32.0;
+            config.channel.differential_gain_pct_per_ire = 0.12;
+            config.channel.differential_phase_deg_per_ire = 0.1;
+            config.channel.line_coupling_us = 400.0;
+            config.receiver.clamp = ClampMode::SyncTip;
+            config.receiver.agc_enabled = true;
+            config.receiver.agc_time_constant_ms = 2.0;
+            config.receiver.acc_enabled = true;
+            config.receiver.color_killer_enabled = true;
         }
         "severe-tracking" => {
             config.tape.tracking_error = 0.4;
             config.artifacts.horizontal_tbc_amplitude = 0.008;
             config.artifacts.vertical_jitter_amplitude = 0.006;
             config.demodulation.filter = DemodulationFilter::Box;
+            config.receiver.clamp = ClampMode::BackPorch;
+            config.receiver.agc_enabled = true;
+            config.receiver.agc_time_constant_ms = 20.0;
         }
         "vintage-camcorder" => {
             config.channel.chroma_bandwidth_mhz = 0.8;
+            config.channel.luma_peaking = 0.6;
+            config.channel.peaking_frequency_mhz = 2.0;
+            config.channel.chroma_noise = 0.04;
             config.precision.vhs_chroma_bandwidth_mhz = 0.6;
             config.artifacts.chroma_phase_drift_depth = 0.4;
             config.demodulation.filter = DemodulationFilter::Notch;
+            config.demodulation.axes = AxisProfile::ConsumerRedPush.axes();
         }
         _ => {
             config.tape.flutter_depth = 0.2;
+            config.channel.luma_peaking = 0.4;
+            config.channel.chroma_noise = 0.03;
+            config.tape.tape_hiss_db = -40.0;
             config.artifacts.chroma_phase_drift_depth = 0.3;
             config.demodulation.filter = DemodulationFilter::Comb1D;
+            config.demodulation.axes = AxisProfile::ConsumerMild.axes();
+            config.channel.yc_delay_ns = 120.0;
         }
     }
     config
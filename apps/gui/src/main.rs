@@ -1,8 +1,10 @@
 use eframe::egui;
 use egui::{ColorImage, TextureHandle};
-use image::{imageops::FilterType, DynamicImage, GenericImageView};
+use image::{imageops::FilterType, DynamicImage};
 use ntscloom_core::{
-    process_frame, process_frame_with_progress, DemodulationFilter, Frame, PipelineConfig,
+    AxisProfile, CameraTransfer, ChromaSubsampling, ClampMode, DemodulationFilter,
+    DisplayTransfer, DropoutCompensation, FilterPrototype, Frame, LegalizerMode, PhosphorMask,
+    Pipeline, PipelineConfig, RasterView, SignalDomain,
 };
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
@@ -96,7 +98,7 @@ impl AppState {
             config.precision.resample_taps = config.precision.preview_resample_taps;
         }
         let frame = image_to_frame(&preview_image);
-        let processed = process_frame(&frame, &config, 14_318_180.0);
+        let processed = render_still(&frame, config, |_| {});
         let color_image = frame_to_color_image(&processed);
         self.preview_texture = Some(ctx.load_texture("preview", color_image, egui::TextureOptions::LINEAR));
     }
@@ -119,7 +121,7 @@ impl AppState {
         let result = self.render_result.clone();
         thread::spawn(move || {
             let frame = image_to_frame(&input);
-            let processed = process_frame_with_progress(&frame, &config, 14_318_180.0, |p| {
+            let processed = render_still(&frame, config, |p| {
                 let value = (p * 100.0) as usize;
                 progress.store(value, Ordering::Relaxed);
             });
@@ -187,18 +189,19 @@ impl AppState {
             .pick_file();
         if let Some(path) = open_path {
             match std::fs::File::open(path) {
-                Ok(file) => {
-                    if let Ok(preset) = serde_json::from_reader::<_, Preset>(file) {
+                Ok(file) => match serde_json::from_reader::<_, Preset>(file) {
+                    Ok(preset) => {
                         self.config = preset.config.clone();
                         self.presets.push(preset);
                         self.selected_preset = self.presets.len() - 1;
                         self.status = Some("Preset loaded.".to_string());
-                    } else {
-                        self.status = Some("Failed to parse preset.".to_string());
                     }
-                }
-                Err(_) => {
-                    self.status = Some("Unable to open preset.".to_string());
+                    Err(err) => {
+                        self.status = Some(format!("Failed to parse preset: {err}"));
+                    }
+                },
+                Err(err) => {
+                    self.status = Some(format!("Unable to open preset: {err}"));
                 }
             }
         }
@@ -267,9 +270,6 @@ impl eframe::App for AppState {
             ui.heading("Preview");
             ui.vertical_centered(|ui| {
                 if let Some(texture) = &self.preview_texture {
-                    let available = ui.available_size();
-                    let image_size = texture.size_vec2();
-                    let scale = (available.x / image_size.x).min(available.y / image_size.y).min(1.0);
                     ui.image(texture);
                 } else {
                     ui.label("Import media to preview.");
@@ -288,7 +288,7 @@ impl eframe::App for AppState {
             let scroll = egui::ScrollArea::vertical()
                 .auto_shrink([false; 2])
                 .id_source("artifact_scroll");
-            let mut output = scroll.show(ui, |ui| {
+            let output = scroll.show(ui, |ui| {
                 egui::CollapsingHeader::new("Composite Encoding").default_open(true).show(ui, |ui| {
                     ui.add(egui::Slider::new(&mut self.config.composite.subcarrier_phase_deg, -180.0..=180.0))
                         .on_hover_text("Phase offset of the NTSC subcarrier.");
@@ -296,17 +296,124 @@ impl eframe::App for AppState {
                         .on_hover_text("Colorburst amplitude per scanline.");
                     ui.add(egui::Slider::new(&mut self.config.composite.chroma_level, 0.0..=2.0))
                         .on_hover_text("Chroma level applied during encoding.");
+                    egui::ComboBox::from_id_source("signal_domain")
+                        .selected_text(format!("Domain: {:?}", self.config.composite.signal_domain))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.config.composite.signal_domain, SignalDomain::GammaEncoded, "Gamma-encoded (R'G'B')");
+                            ui.selectable_value(&mut self.config.composite.signal_domain, SignalDomain::Linear, "Linear light");
+                        });
+                    egui::ComboBox::from_id_source("camera_transfer")
+                        .selected_text(format!("Camera: {:?}", self.config.composite.camera_transfer))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.config.composite.camera_transfer, CameraTransfer::Bt601, "BT.601");
+                            ui.selectable_value(&mut self.config.composite.camera_transfer, CameraTransfer::Srgb, "sRGB");
+                        });
+                    ui.add(egui::Slider::new(&mut self.config.composite.setup_ire, 0.0..=10.0))
+                        .on_hover_text("Setup (black pedestal) in IRE: 7.5 for NTSC-M, 0 for NTSC-J.");
+                    egui::ComboBox::from_id_source("legalizer")
+                        .selected_text(format!("Legalizer: {:?}", self.config.composite.legalizer))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.config.composite.legalizer, LegalizerMode::Off, "Off");
+                            ui.selectable_value(&mut self.config.composite.legalizer, LegalizerMode::Clip, "Encoder clipper");
+                            ui.selectable_value(&mut self.config.composite.legalizer, LegalizerMode::ReduceChroma, "Reduce chroma");
+                        });
+                    ui.add(egui::Slider::new(&mut self.config.composite.legal_max_ire, 100.0..=140.0))
+                        .on_hover_text("Upper composite limit in IRE.");
+                    ui.add(egui::Slider::new(&mut self.config.composite.legal_min_ire, -40.0..=0.0))
+                        .on_hover_text("Lower composite limit in IRE.");
                 });
 
+                egui::CollapsingHeader::new("Copy Protection").default_open(false).show(ui, |ui| {
+                    let protection = &mut self.config.copy_protection;
+                    ui.checkbox(&mut protection.agc_pulses_enabled, "AGC pulses")
+                        .on_hover_text("Pseudo-sync / AGC pulse pairs in the VBI (needs receiver AGC).");
+                    ui.add(egui::Slider::new(&mut protection.agc_pulse_first_line, 10..=21))
+                        .on_hover_text("First VBI line carrying pulses.");
+                    ui.add(egui::Slider::new(&mut protection.agc_pulse_last_line, 10..=21))
+                        .on_hover_text("Last VBI line carrying pulses.");
+                    ui.add(egui::Slider::new(&mut protection.agc_pulses_per_line, 1..=8))
+                        .on_hover_text("Pulse pairs per line.");
+                    ui.add(egui::Slider::new(&mut protection.agc_pulse_peak_ire, 0.0..=130.0))
+                        .on_hover_text("AGC pulse peak level in IRE.");
+                    ui.add(egui::Slider::new(&mut protection.agc_cycle_s, 0.0..=10.0))
+                        .on_hover_text("Pulse amplitude cycle in seconds (0 = constant).");
+                    ui.checkbox(&mut protection.colorstripe_enabled, "Colorstripe")
+                        .on_hover_text("Shift the burst phase on groups of lines.");
+                    ui.add(egui::Slider::new(&mut protection.colorstripe_lines_on, 1..=8))
+                        .on_hover_text("Striped lines per group.");
+                    ui.add(egui::Slider::new(&mut protection.colorstripe_period_lines, 2..=64))
+                        .on_hover_text("Lines per colorstripe group.");
+                    ui.add(egui::Slider::new(&mut protection.colorstripe_phase_deg, -180.0..=180.0))
+                        .on_hover_text("Burst phase shift on striped lines.");
+                });
+
+                egui::CollapsingHeader::new("VITC").default_open(false).show(ui, |ui| {
+                    let vitc = &mut self.config.vitc;
+                    ui.checkbox(&mut vitc.enabled, "Insert timecode")
+                        .on_hover_text("SMPTE 12M vertical interval timecode from the frame index.");
+                    ui.checkbox(&mut vitc.drop_frame, "Drop frame")
+                        .on_hover_text("Drop-frame numbering that keeps pace with 29.97 fps.");
+                    for line in vitc.lines.iter_mut() {
+                        ui.add(egui::Slider::new(line, 10..=21))
+                            .on_hover_text("VBI line carrying the timecode.");
+                    }
+                });
+
+                egui::CollapsingHeader::new("VITS").default_open(false).show(ui, |ui| {
+                    let vits = &mut self.config.vits;
+                    ui.checkbox(&mut vits.enabled, "Insert test signals")
+                        .on_hover_text("Vertical interval test signals, measured after the channel.");
+                    ui.add(egui::Slider::new(&mut vits.multiburst_line, 0..=21).text("Multiburst line"))
+                        .on_hover_text("0 = off.");
+                    ui.add(egui::Slider::new(&mut vits.staircase_line, 0..=21).text("Staircase line"))
+                        .on_hover_text("Modulated staircase; 0 = off.");
+                    ui.add(egui::Slider::new(&mut vits.pulse_bar_line, 0..=21).text("Pulse & bar line"))
+                        .on_hover_text("2T pulse and bar; 0 = off.");
+                    ui.add(egui::Slider::new(&mut vits.composite_line, 0..=21).text("Composite line"))
+                        .on_hover_text("Bar, 2T, 12.5T chroma pulse and staircase; 0 = off.");
+                });
+                for conflict in self.config.vbi_conflicts(false) {
+                    ui.colored_label(egui::Color32::YELLOW, conflict.to_string());
+                }
+
                 egui::CollapsingHeader::new("Channel Filters").default_open(true).show(ui, |ui| {
                     ui.add(egui::Slider::new(&mut self.config.channel.luma_bandwidth_mhz, 0.1..=8.0))
                         .on_hover_text("Luma bandwidth in MHz.");
                     ui.add(egui::Slider::new(&mut self.config.channel.chroma_bandwidth_mhz, 0.1..=6.0))
                         .on_hover_text("Chroma bandwidth in MHz.");
+                    ui.add(egui::Slider::new(&mut self.config.channel.yc_delay_ns, -500.0..=500.0))
+                        .on_hover_text("Y/C delay in ns (positive = colour lands right of the luma edge).");
+                    egui::ComboBox::from_id_source("filter_prototype")
+                        .selected_text(format!("{:?}", self.config.channel.filter_prototype))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.config.channel.filter_prototype, FilterPrototype::Butterworth, "Butterworth");
+                            ui.selectable_value(&mut self.config.channel.filter_prototype, FilterPrototype::Bessel, "Bessel");
+                            ui.selectable_value(&mut self.config.channel.filter_prototype, FilterPrototype::Gaussian, "Gaussian");
+                        });
+                    ui.add(egui::Slider::new(&mut self.config.channel.filter_order, 1..=8))
+                        .on_hover_text("Lowpass order (slope, 6 dB/octave per order).");
+                    ui.add(egui::Slider::new(&mut self.config.channel.differential_gain_pct_per_ire, -0.3..=0.3))
+                        .on_hover_text("Differential gain: chroma gain change in % per IRE of luma.");
+                    ui.add(egui::Slider::new(&mut self.config.channel.differential_phase_deg_per_ire, -0.3..=0.3))
+                        .on_hover_text("Differential phase: chroma phase shift in degrees per IRE of luma.");
+                    ui.add(egui::Slider::new(&mut self.config.channel.differential_curve_exponent, 0.5..=4.0))
+                        .on_hover_text("DG/DP curve shape (1 = linear, higher = mostly in highlights).");
                     ui.add(egui::Slider::new(&mut self.config.channel.luma_ringing, 0.0..=1.0))
-                        .on_hover_text("Luma ringing/overshoot amount.");
+                        .on_hover_text("Gibbs ringing from a brick-wall luma band limit.");
+                    ui.add(egui::Slider::new(&mut self.config.channel.luma_peaking, 0.0..=2.0))
+                        .on_hover_text("Peaking/sharpness gain (overshoot and undershoot halos).");
+                    ui.add(egui::Slider::new(&mut self.config.channel.peaking_frequency_mhz, 0.5..=5.0))
+                        .on_hover_text("Peaking centre frequency in MHz.");
                     ui.add(egui::Slider::new(&mut self.config.channel.luma_noise, 0.0..=1.0))
-                        .on_hover_text("Luma noise level.");
+                        .on_hover_text("Luma noise level (Gaussian, band-limited to luma bandwidth).");
+                    ui.add(egui::Slider::new(&mut self.config.channel.chroma_noise, 0.0..=0.5))
+                        .on_hover_text("Chroma noise level (colour confetti).");
+                    ui.add(egui::Slider::new(&mut self.config.channel.color_noise_texture_scale, 0.1..=8.0))
+                        .on_hover_text("Colour noise texture scale (larger = longer streaks).");
+                    ui.add(egui::Slider::new(&mut self.config.channel.line_coupling_us, 0.0..=2000.0).logarithmic(true))
+                        .on_hover_text("Line coupling time constant in µs (line tilt, streaks; 0 = DC coupled).");
+                    ui.add(egui::Slider::new(&mut self.config.channel.field_coupling_ms, 0.0..=200.0).logarithmic(true))
+                        .on_hover_text("Field coupling time constant in ms (field tilt, bounce; 0 = DC coupled).");
                     ui.add(egui::Slider::new(&mut self.config.channel.dot_crawl_intensity, 0.0..=1.0))
                         .on_hover_text("Dot crawl intensity.");
                 });
@@ -322,6 +429,17 @@ impl eframe::App for AppState {
                         .on_hover_text("Dropout frequency.");
                     ui.add(egui::Slider::new(&mut self.config.tape.head_switch_jitter, 0.0..=1.0))
                         .on_hover_text("Head switch timing jitter.");
+                    ui.add(egui::Slider::new(&mut self.config.tape.tape_hiss_db, -60.0..=0.0))
+                        .on_hover_text("Tape hiss (1/f noise) level in dB.");
+                    ui.add(egui::Slider::new(&mut self.config.tape.dropout_threshold, 0.0..=1.0))
+                        .on_hover_text("RF envelope level below which the dropout detector fires.");
+                    egui::ComboBox::from_id_source("dropout_compensation")
+                        .selected_text(format!("{:?}", self.config.tape.dropout_compensation))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.config.tape.dropout_compensation, DropoutCompensation::Doc, "DOC (1H delay)");
+                            ui.selectable_value(&mut self.config.tape.dropout_compensation, DropoutCompensation::WhiteSparkles, "No DOC (white sparkles)");
+                            ui.selectable_value(&mut self.config.tape.dropout_compensation, DropoutCompensation::BlackSparkles, "No DOC (black sparkles)");
+                        });
                 });
 
                 egui::CollapsingHeader::new("Artifacts").default_open(false).show(ui, |ui| {
@@ -377,6 +495,8 @@ impl eframe::App for AppState {
                             ui.selectable_value(&mut self.config.demodulation.filter, DemodulationFilter::Notch, "Notch");
                             ui.selectable_value(&mut self.config.demodulation.filter, DemodulationFilter::Comb1D, "1D Comb");
                             ui.selectable_value(&mut self.config.demodulation.filter, DemodulationFilter::Comb2D, "2D Comb");
+                            ui.selectable_value(&mut self.config.demodulation.filter, DemodulationFilter::AdaptiveComb, "Adaptive Comb");
+                            ui.selectable_value(&mut self.config.demodulation.filter, DemodulationFilter::Comb3D, "3D Comb");
                         });
                     ui.add(egui::Slider::new(&mut self.config.demodulation.box_kernel, 1..=9))
                         .on_hover_text("Box filter kernel size.");
@@ -386,6 +506,133 @@ impl eframe::App for AppState {
                         .on_hover_text("Notch depth.");
                     ui.add(egui::Slider::new(&mut self.config.demodulation.comb_strength, 0.0..=1.0))
                         .on_hover_text("Comb filter strength.");
+                    ui.add(egui::Slider::new(&mut self.config.demodulation.adaptive_threshold, 0.01..=0.5))
+                        .on_hover_text("Line mismatch at which the adaptive comb falls back to notch separation.");
+                    ui.add(egui::Slider::new(&mut self.config.demodulation.motion_threshold, 0.01..=0.5))
+                        .on_hover_text("Frame-to-frame difference at which the 3D comb treats a pixel as moving.");
+                    egui::ComboBox::from_id_source("decoder_axes")
+                        .selected_text(
+                            AxisProfile::ALL
+                                .iter()
+                                .find(|profile| profile.axes() == self.config.demodulation.axes)
+                                .map_or("Custom", |profile| profile.name()),
+                        )
+                        .show_ui(ui, |ui| {
+                            for profile in AxisProfile::ALL {
+                                if ui.selectable_label(profile.axes() == self.config.demodulation.axes, profile.name()).clicked() {
+                                    self.config.demodulation.axes = profile.axes();
+                                }
+                            }
+                        });
+                    let axes = &mut self.config.demodulation.axes;
+                    ui.add(egui::Slider::new(&mut axes.first.angle_deg, -30.0..=180.0).text("Demod 1 angle"))
+                        .on_hover_text("First demodulator angle from B-Y (I = 123°, B-Y = 0°).");
+                    ui.add(egui::Slider::new(&mut axes.first.gain, 0.5..=1.5).text("Demod 1 gain"));
+                    ui.add(egui::Slider::new(&mut axes.first.bandwidth_mhz, 0.1..=2.0).text("Demod 1 bandwidth"));
+                    ui.add(egui::Slider::new(&mut axes.second.angle_deg, -30.0..=180.0).text("Demod 2 angle"))
+                        .on_hover_text("Second demodulator angle from B-Y (Q = 33°, R-Y = 90°).");
+                    ui.add(egui::Slider::new(&mut axes.second.gain, 0.5..=1.5).text("Demod 2 gain"));
+                    ui.add(egui::Slider::new(&mut axes.second.bandwidth_mhz, 0.1..=2.0).text("Demod 2 bandwidth"));
+                });
+
+                egui::CollapsingHeader::new("Receiver").default_open(false).show(ui, |ui| {
+                    egui::ComboBox::from_id_source("receiver_clamp")
+                        .selected_text(format!("Clamp: {:?}", self.config.receiver.clamp))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.config.receiver.clamp, ClampMode::Off, "Off");
+                            ui.selectable_value(&mut self.config.receiver.clamp, ClampMode::SyncTip, "Sync tip");
+                            ui.selectable_value(&mut self.config.receiver.clamp, ClampMode::BackPorch, "Back porch");
+                        });
+                    ui.checkbox(&mut self.config.receiver.agc_enabled, "AGC")
+                        .on_hover_text("Closed-loop gain control on the measured sync amplitude.");
+                    ui.add(egui::Slider::new(&mut self.config.receiver.agc_reference_ire, 10.0..=80.0))
+                        .on_hover_text("Sync amplitude the AGC aims for, in IRE (40 = nominal).");
+                    ui.add(egui::Slider::new(&mut self.config.receiver.agc_time_constant_ms, 0.05..=100.0).logarithmic(true))
+                        .on_hover_text("AGC time constant in ms (short = pumping on noise and dropouts).");
+                    ui.checkbox(&mut self.config.receiver.acc_enabled, "ACC")
+                        .on_hover_text("Automatic chroma control: scale chroma by nominal / measured burst.");
+                    ui.add(egui::Slider::new(&mut self.config.receiver.acc_max_gain, 1.0..=8.0))
+                        .on_hover_text("Largest chroma boost the ACC applies.");
+                    ui.add(egui::Slider::new(&mut self.config.receiver.burst_detector_ms, 0.1..=50.0).logarithmic(true))
+                        .on_hover_text("Burst amplitude detector time constant in ms (ACC and colour killer).");
+                    ui.checkbox(&mut self.config.receiver.color_killer_enabled, "Colour killer")
+                        .on_hover_text("Switch chroma off when the burst is too weak.");
+                    ui.add(egui::Slider::new(&mut self.config.receiver.color_killer_off_pct, 0.0..=100.0))
+                        .on_hover_text("Burst level (% of nominal) below which colour is killed.");
+                    ui.add(egui::Slider::new(&mut self.config.receiver.color_killer_on_pct, 0.0..=100.0))
+                        .on_hover_text("Burst level (% of nominal) above which colour comes back (hysteresis).");
+                    ui.add(egui::Slider::new(&mut self.config.receiver.brightness, -0.5..=0.5))
+                        .on_hover_text("Brightness (black level).");
+                    ui.add(egui::Slider::new(&mut self.config.receiver.contrast, 0.0..=3.0))
+                        .on_hover_text("Contrast / picture (video amplifier gain, clips when overdriven).");
+                    ui.add(egui::Slider::new(&mut self.config.receiver.color, 0.0..=3.0))
+                        .on_hover_text("Colour (chroma amplifier gain).");
+                    ui.add(egui::Slider::new(&mut self.config.receiver.tint_deg, -60.0..=60.0))
+                        .on_hover_text("Tint: demodulator phase; positive = greener flesh tones.");
+                    ui.add(egui::Slider::new(&mut self.config.receiver.sharpness, -1.0..=2.0))
+                        .on_hover_text("Sharpness (luma peaking; negative softens).");
+                    egui::ComboBox::from_id_source("display_transfer")
+                        .selected_text(format!("Display: {:?}", self.config.receiver.display_transfer))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.config.receiver.display_transfer, DisplayTransfer::Crt22, "CRT gamma 2.2");
+                            ui.selectable_value(&mut self.config.receiver.display_transfer, DisplayTransfer::Crt25, "CRT gamma 2.5");
+                            ui.selectable_value(&mut self.config.receiver.display_transfer, DisplayTransfer::Srgb, "sRGB");
+                        });
+                    ui.add(egui::Slider::new(&mut self.config.receiver.setup_ire, 0.0..=10.0))
+                        .on_hover_text("Black level the set is aligned for, in IRE (mismatch lifts or crushes blacks).");
+                });
+
+                egui::CollapsingHeader::new("Capture").default_open(false).show(ui, |ui| {
+                    let capture = &mut self.config.capture;
+                    ui.checkbox(&mut capture.enabled, "Digitizer")
+                        .on_hover_text("Capture card ADC between the clamp and the decoder.");
+                    ui.add(egui::Slider::new(&mut capture.bits, 1..=12))
+                        .on_hover_text("ADC bit depth over the input range.");
+                    ui.add(egui::Slider::new(&mut capture.input_min_ire, -60.0..=20.0))
+                        .on_hover_text("Bottom of the input range in IRE (above 7.5 clips superblacks and black).");
+                    ui.add(egui::Slider::new(&mut capture.input_max_ire, 60.0..=150.0))
+                        .on_hover_text("Top of the input range in IRE.");
+                    ui.add(egui::Slider::new(&mut capture.clock_jitter_ps, 0.0..=20_000.0).logarithmic(true))
+                        .on_hover_text("RMS sampling clock jitter in picoseconds.");
+                    egui::ComboBox::from_id_source("chroma_subsampling")
+                        .selected_text(format!("Chroma: {:?}", capture.chroma_subsampling))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut capture.chroma_subsampling, ChromaSubsampling::Full444, "4:4:4");
+                            ui.selectable_value(&mut capture.chroma_subsampling, ChromaSubsampling::Sub422, "4:2:2");
+                            ui.selectable_value(&mut capture.chroma_subsampling, ChromaSubsampling::Sub411, "4:1:1");
+                        });
+                });
+
+                egui::CollapsingHeader::new("CRT Display").default_open(false).show(ui, |ui| {
+                    let crt = &mut self.config.crt;
+                    ui.checkbox(&mut crt.enabled, "CRT")
+                        .on_hover_text("Draw the decoded picture on a simulated CRT at an upscaled resolution.");
+                    ui.add(egui::Slider::new(&mut crt.scale, 1..=6))
+                        .on_hover_text("Output pixels per input pixel.");
+                    ui.add(egui::Slider::new(&mut crt.spot_dark, 0.1..=1.0))
+                        .on_hover_text("Beam spot size at black, in scanline pitches (small = scanline gaps).");
+                    ui.add(egui::Slider::new(&mut crt.spot_bright, 0.1..=1.0))
+                        .on_hover_text("Beam spot size at full drive; bright lines swell over the gaps.");
+                    egui::ComboBox::from_id_source("phosphor_mask")
+                        .selected_text(format!("Mask: {:?}", crt.mask))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut crt.mask, PhosphorMask::None, "None");
+                            ui.selectable_value(&mut crt.mask, PhosphorMask::ApertureGrille, "Aperture grille");
+                            ui.selectable_value(&mut crt.mask, PhosphorMask::SlotMask, "Slot mask");
+                            ui.selectable_value(&mut crt.mask, PhosphorMask::ShadowMask, "Shadow mask");
+                        });
+                    ui.add(egui::Slider::new(&mut crt.mask_pitch, 1.0..=12.0))
+                        .on_hover_text("Phosphor triad pitch in output pixels.");
+                    ui.add(egui::Slider::new(&mut crt.mask_strength, 0.0..=1.0))
+                        .on_hover_text("How dark the mask is between phosphors.");
+                    ui.add(egui::Slider::new(&mut crt.halation, 0.0..=0.5))
+                        .on_hover_text("Share of the light scattered in the faceplate glass.");
+                    ui.add(egui::Slider::new(&mut crt.halation_radius, 1.0..=32.0))
+                        .on_hover_text("Halation radius in input pixels.");
+                    ui.add(egui::Slider::new(&mut crt.bloom, 0.0..=1.0))
+                        .on_hover_text("Glow around highlights.");
+                    ui.add(egui::Slider::new(&mut crt.bloom_radius, 0.5..=8.0))
+                        .on_hover_text("Bloom radius in input pixels.");
                 });
 
                 egui::CollapsingHeader::new("Precision & Resampling").default_open(false).show(ui, |ui| {
@@ -418,6 +665,19 @@ impl eframe::App for AppState {
                         .on_hover_text("Render I/Q visualization overlay.");
                     ui.checkbox(&mut self.config.debug.show_grid, "Show diagnostic grid")
                         .on_hover_text("Overlay sample/grid lines for debugging.");
+                    ui.checkbox(&mut self.config.debug.show_comb_blend, "Show adaptive comb blend")
+                        .on_hover_text("Red = comb with line above, blue = line below, green = notch fallback, white = frame comb.");
+                    ui.checkbox(&mut self.config.debug.show_illegal, "Show illegal colours")
+                        .on_hover_text("Magenta where the input's composite excursion is outside the legal range.");
+                    egui::ComboBox::from_id_source("raster_view")
+                        .selected_text(format!("Raster: {:?}", self.config.debug.raster_view))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.config.debug.raster_view, RasterView::Active, "Active picture");
+                            ui.selectable_value(&mut self.config.debug.raster_view, RasterView::RawComposite, "Full raster, raw composite");
+                            ui.selectable_value(&mut self.config.debug.raster_view, RasterView::Underscan, "Full raster, underscan decode");
+                        })
+                        .response
+                        .on_hover_text("Show the whole 910x525 raster with blanking, sync, burst and VBI.");
                 });
 
                 egui::CollapsingHeader::new("Output").default_open(false).show(ui, |ui| {
@@ -446,31 +706,54 @@ fn default_presets() -> Vec<Preset> {
     clean.tape.flutter_depth = 0.02;
     clean.artifacts.head_switch_intensity = 0.1;
     clean.artifacts.dropout_rate = 0.0;
-    clean.demodulation.filter = DemodulationFilter::Comb2D;
+    clean.demodulation.filter = DemodulationFilter::Comb3D;
 
     let mut consumer = PipelineConfig::default();
     consumer.tape.flutter_depth = 0.2;
+    consumer.channel.luma_peaking = 0.4;
+    consumer.channel.chroma_noise = 0.03;
+    consumer.tape.tape_hiss_db = -40.0;
     consumer.artifacts.chroma_phase_drift_depth = 0.3;
     consumer.demodulation.filter = DemodulationFilter::Comb1D;
+    consumer.demodulation.axes = AxisProfile::ConsumerMild.axes();
+    consumer.channel.yc_delay_ns = 120.0;
 
     let mut damaged = PipelineConfig::default();
     damaged.tape.dropout_rate = 0.08;
     damaged.artifacts.dropout_rate = 0.08;
     damaged.artifacts.head_switch_intensity = 0.6;
     damaged.artifacts.saturation_strength = 0.5;
+    damaged.channel.chroma_noise = 0.06;
+    damaged.channel.color_noise_texture_scale = 3.0;
+    damaged.tape.tape_hiss_db = -32.0;
+    damaged.channel.differential_gain_pct_per_ire = 0.12;
+    damaged.channel.differential_phase_deg_per_ire = 0.1;
+    damaged.channel.line_coupling_us = 400.0;
     damaged.demodulation.filter = DemodulationFilter::Lowpass;
+    damaged.receiver.clamp = ClampMode::SyncTip;
+    damaged.receiver.agc_enabled = true;
+    damaged.receiver.agc_time_constant_ms = 2.0;
+    damaged.receiver.acc_enabled = true;
+    damaged.receiver.color_killer_enabled = true;
 
     let mut severe = PipelineConfig::default();
     severe.tape.tracking_error = 0.4;
     severe.artifacts.horizontal_tbc_amplitude = 0.008;
     severe.artifacts.vertical_jitter_amplitude = 0.006;
     severe.demodulation.filter = DemodulationFilter::Box;
+    severe.receiver.clamp = ClampMode::BackPorch;
+    severe.receiver.agc_enabled = true;
+    severe.receiver.agc_time_constant_ms = 20.0;
 
     let mut camcorder = PipelineConfig::default();
     camcorder.channel.chroma_bandwidth_mhz = 0.8;
+    camcorder.channel.luma_peaking = 0.6;
+    camcorder.channel.peaking_frequency_mhz = 2.0;
+    camcorder.channel.chroma_noise = 0.04;
     camcorder.precision.vhs_chroma_bandwidth_mhz = 0.6;
     camcorder.artifacts.chroma_phase_drift_depth = 0.4;
     camcorder.demodulation.filter = DemodulationFilter::Notch;
+    camcorder.demodulation.axes = AxisProfile::ConsumerRedPush.axes();
 
     vec![
         Preset {
@@ -496,6 +779,20 @@ fn default_presets() -> Vec<Preset> {
     ]
 }
 
+/// Renders a still as the second frame of a static sequence, so the 3D comb has the
+/// previous frame to comb against instead of falling back to its 2D separation.
+fn render_still<F>(frame: &Frame, config: PipelineConfig, on_progress: F) -> Frame
+where
+    F: FnMut(f32),
+{
+    let lead_in = config.demodulation.filter == DemodulationFilter::Comb3D;
+    let mut pipeline = Pipeline::new(config, 14_318_180.0);
+    if lead_in {
+        pipeline.process(frame);
+    }
+    pipeline.process_with_progress(frame, on_progress)
+}
+
 fn image_to_frame(image: &DynamicImage) -> Frame {
     let rgb = image.to_rgb8();
     let (width, height) = rgb.dimensions();
@@ -0,0 +1,343 @@
+//! CEA-608 closed captions on line 21: caption byte streams from SCC or SRT files, the
+//! line 21 data waveform and the data slicer that reads it back.
+
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::pipeline::FRAME_RATE_HZ;
+use crate::timecode::Timecode;
+
+/// Field 1 caption line (1-based, within the VBI).
+pub const CAPTION_LINE: usize = 21;
+/// Padding sent on frames without caption data: two parity-coded zero bytes.
+pub const NULL_PAIR: [u8; 2] = [0x80, 0x80];
+
+/// The bit rate is 32 times the line rate, so a nominal 52.6 µs active line holds 26.5 bit
+/// periods; narrower frames scale the whole waveform with their line.
+const BITS_PER_ACTIVE_LINE: f32 = 26.5;
+/// Clock run-in starts 10.5 µs after the sync edge, about half a bit into the active line.
+const RUN_IN_START_BITS: f32 = 0.5;
+const RUN_IN_CYCLES: usize = 7;
+/// Two zero start bits, then a one.
+const START_BITS: [bool; 3] = [false, false, true];
+const DATA_HIGH: f32 = 0.5;
+/// Run-in swing below which the slicer reports no data (half the nominal 50 IRE).
+const MIN_RUN_IN_SWING: f32 = 0.25;
+
+const MAX_ROW_CHARS: usize = 32;
+const RESUME_CAPTION_LOADING: [u8; 2] = [0x14, 0x20];
+const ERASE_DISPLAYED_MEMORY: [u8; 2] = [0x14, 0x2c];
+const ERASE_NON_DISPLAYED_MEMORY: [u8; 2] = [0x14, 0x2e];
+const END_OF_CAPTION: [u8; 2] = [0x14, 0x2f];
+/// Preamble address codes for rows 14 and 15, white, no indent.
+const ROW_PREAMBLES: [[u8; 2]; 2] = [[0x14, 0x40], [0x14, 0x60]];
+/// Words closing a pop-on caption: erase displayed memory and end of caption, doubled.
+const POP_ON_TAIL_WORDS: usize = 4;
+
+#[derive(Debug, Error)]
+pub enum CaptionError {
+    #[error("caption file line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("unsupported caption file type (expected .scc or .srt): {0}")]
+    UnsupportedFormat(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+fn parse_error(line: usize, message: impl Into<String>) -> CaptionError {
+    CaptionError::Parse {
+        line: line + 1,
+        message: message.into(),
+    }
+}
+
+/// Field 1 caption data: one byte pair per frame from frame 0, parity bits included.
+/// Frames past the end carry null padding.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CaptionTrack {
+    pairs: Vec<[u8; 2]>,
+}
+
+impl CaptionTrack {
+    pub fn from_pairs(pairs: Vec<[u8; 2]>) -> Self {
+        Self { pairs }
+    }
+
+    pub fn pairs(&self) -> &[[u8; 2]] {
+        &self.pairs
+    }
+
+    pub fn pair(&self, frame_index: u64) -> [u8; 2] {
+        self.pairs.get(frame_index as usize).copied().unwrap_or(NULL_PAIR)
+    }
+
+    /// Loads an `.scc` or `.srt` file, picked by extension.
+    pub fn load(path: &Path) -> Result<Self, CaptionError> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        let text = std::fs::read_to_string(path)?;
+        match extension.as_str() {
+            "scc" => Self::from_scc(&text),
+            "srt" => Self::from_srt(&text),
+            _ => Err(CaptionError::UnsupportedFormat(path.display().to_string())),
+        }
+    }
+
+    /// Scenarist SCC: each `timecode<TAB>word word ...` line sends its hex words on
+    /// consecutive frames from the timecode (or from the end of the previous line's data,
+    /// if that is later). Words already carry their parity bits.
+    pub fn from_scc(text: &str) -> Result<Self, CaptionError> {
+        let mut track = Self::default();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("Scenarist_SCC") {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let timecode = fields.next().unwrap_or_default();
+            let frame = timecode
+                .parse::<Timecode>()
+                .map_err(|_| parse_error(n, format!("bad timecode {timecode:?}")))?
+                .frame_index() as usize;
+            let words = fields
+                .map(|word| {
+                    u16::from_str_radix(word, 16)
+                        .ok()
+                        .filter(|_| word.len() == 4)
+                        .map(|value| value.to_be_bytes())
+                        .ok_or_else(|| parse_error(n, format!("bad caption word {word:?}")))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            track.send_at(frame, &words);
+        }
+        Ok(track)
+    }
+
+    /// SubRip: every cue becomes a pop-on caption on rows 14-15, loaded off screen so that
+    /// its end-of-caption lands on the cue's start, and erased at the cue's end unless the
+    /// next cue pops on by then.
+    pub fn from_srt(text: &str) -> Result<Self, CaptionError> {
+        let mut cues = Vec::new();
+        let lines: Vec<&str> = text.lines().map(|line| line.trim_end_matches('\r')).collect();
+        let mut n = 0;
+        while n < lines.len() {
+            let Some(arrow) = lines[n].find("-->") else {
+                n += 1;
+                continue;
+            };
+            let start =
+                parse_srt_time(&lines[n][..arrow]).ok_or_else(|| parse_error(n, "bad cue start"))?;
+            let end =
+                parse_srt_time(&lines[n][arrow + 3..]).ok_or_else(|| parse_error(n, "bad cue end"))?;
+            n += 1;
+            let mut rows = Vec::new();
+            while n < lines.len() && !lines[n].trim().is_empty() {
+                rows.push(lines[n].trim());
+                n += 1;
+            }
+
+            let mut words = vec![RESUME_CAPTION_LOADING, ERASE_NON_DISPLAYED_MEMORY];
+            let first_row = ROW_PREAMBLES.len().saturating_sub(rows.len());
+            for (row, text) in rows.iter().take(ROW_PREAMBLES.len()).enumerate() {
+                words.push(ROW_PREAMBLES[first_row + row]);
+                let chars: Vec<u8> = text.chars().take(MAX_ROW_CHARS).map(caption_char).collect();
+                words.extend(
+                    chars.chunks(2).map(|pair| [pair[0], pair.get(1).copied().unwrap_or(0)]),
+                );
+            }
+            words.extend([ERASE_DISPLAYED_MEMORY, END_OF_CAPTION]);
+            // Control codes go out twice, so a single damaged copy is not acted on.
+            let words: Vec<[u8; 2]> = words
+                .into_iter()
+                .flat_map(|word| {
+                    let copies = if word[0] < 0x20 { 2 } else { 1 };
+                    std::iter::repeat_n(word, copies)
+                })
+                .map(|word| word.map(with_parity))
+                .collect();
+
+            cues.push((seconds_to_frame(start), seconds_to_frame(end), words));
+        }
+
+        let mut track = Self::default();
+        let erase = ERASE_DISPLAYED_MEMORY.map(with_parity);
+        for (n, (start, end, words)) in cues.iter().enumerate() {
+            track.send_before(*start, words);
+            // The next caption's own erase takes this one down as it pops on, so a separate
+            // erase only goes out if it lands before that.
+            let next_start = cues.get(n + 1).map_or(usize::MAX, |cue| cue.0);
+            if end + 2 + POP_ON_TAIL_WORDS <= next_start {
+                track.send_at(*end, &[erase, erase]);
+            }
+        }
+        Ok(track)
+    }
+
+    fn send_at(&mut self, frame: usize, words: &[[u8; 2]]) {
+        if self.pairs.len() < frame {
+            self.pairs.resize(frame, NULL_PAIR);
+        }
+        self.pairs.extend_from_slice(words);
+    }
+
+    /// Sends `words` in order on the last padding frames before `frame`, skipping frames
+    /// that already carry data, so the last word goes out on `frame - 1` when it is free.
+    /// Words that do not fit before `frame` follow on the next padding frames after it.
+    fn send_before(&mut self, frame: usize, words: &[[u8; 2]]) {
+        let pairs = &self.pairs;
+        let free = |f: &usize| pairs.get(*f).is_none_or(|pair| *pair == NULL_PAIR);
+        let mut slots: Vec<usize> = (0..frame).rev().filter(free).take(words.len()).collect();
+        slots.extend((frame..).filter(free).take(words.len() - slots.len()));
+        slots.sort_unstable();
+        if let Some(&last) = slots.last() {
+            if self.pairs.len() <= last {
+                self.pairs.resize(last + 1, NULL_PAIR);
+            }
+        }
+        for (slot, word) in slots.into_iter().zip(words) {
+            self.pairs[slot] = *word;
+        }
+    }
+}
+
+fn seconds_to_frame(seconds: f32) -> usize {
+    (seconds * FRAME_RATE_HZ).round().max(0.0) as usize
+}
+
+/// `HH:MM:SS,mmm` to seconds.
+fn parse_srt_time(time: &str) -> Option<f32> {
+    let (clock, millis) = time.trim().split_once([',', '.'])?;
+    let fields: Vec<f32> = clock
+        .split(':')
+        .map(|field| field.parse().ok())
+        .collect::<Option<_>>()?;
+    let [hours, minutes, seconds] = fields[..] else {
+        return None;
+    };
+    Some(hours * 3600.0 + minutes * 60.0 + seconds + millis.parse::<f32>().ok()? / 1000.0)
+}
+
+/// Basic North American character set; the few code points it redefines and anything
+/// outside printable ASCII are sent as spaces.
+fn caption_char(c: char) -> u8 {
+    match c {
+        '*' | '\\' | '^' | '_' | '`' | '{' | '|' | '}' | '~' => b' ',
+        ' '..='\x7e' => c as u8,
+        _ => b' ',
+    }
+}
+
+/// Sets bit 7 so the byte has odd parity.
+pub fn with_parity(byte: u8) -> u8 {
+    let data = byte & 0x7f;
+    if data.count_ones().is_multiple_of(2) {
+        data | 0x80
+    } else {
+        data
+    }
+}
+
+pub fn parity_ok(byte: u8) -> bool {
+    !byte.count_ones().is_multiple_of(2)
+}
+
+/// Line 21 active-region levels for `pair`: seven cycles of clock run-in, the start bits and
+/// 16 data bits LSB first, between blanking and 50 IRE.
+pub fn line21_waveform(pair: [u8; 2], active_len: usize) -> Vec<f32> {
+    let bit_len = active_len as f32 / BITS_PER_ACTIVE_LINE;
+    let bits: Vec<bool> = START_BITS
+        .into_iter()
+        .chain((0..16).map(|k| pair[k / 8] >> (k % 8) & 1 == 1))
+        .collect();
+    (0..active_len)
+        .map(|s| {
+            let t = (s as f32 + 0.5) / bit_len - RUN_IN_START_BITS;
+            if t < 0.0 {
+                0.0
+            } else if t < RUN_IN_CYCLES as f32 {
+                0.5 * DATA_HIGH * (1.0 - (std::f32::consts::TAU * t).cos())
+            } else {
+                match bits.get((t - RUN_IN_CYCLES as f32) as usize) {
+                    Some(true) => DATA_HIGH,
+                    _ => 0.0,
+                }
+            }
+        })
+        .collect()
+}
+
+/// Data slicer: sets the threshold halfway across the clock run-in, times the bits from the
+/// rising edge of the last start bit and samples each data bit at its centre. Returns `None`
+/// when there is no run-in or no start bit to lock to.
+pub fn read_line21(active: &[f32]) -> Option<[u8; 2]> {
+    let bit_len = active.len() as f32 / BITS_PER_ACTIVE_LINE;
+    let at = |bits: f32| ((bits * bit_len) as usize).min(active.len());
+    let run_in = &active[at(RUN_IN_START_BITS)..at(RUN_IN_START_BITS + RUN_IN_CYCLES as f32)];
+    let high = run_in.iter().cloned().fold(f32::MIN, f32::max);
+    let low = run_in.iter().cloned().fold(f32::MAX, f32::min);
+    if run_in.is_empty() || high - low < MIN_RUN_IN_SWING {
+        return None;
+    }
+    let threshold = 0.5 * (high + low);
+
+    let edge_bits = RUN_IN_START_BITS + (RUN_IN_CYCLES + START_BITS.len() - 1) as f32;
+    let search = at(edge_bits - 1.0).max(1)..at(edge_bits + 1.0);
+    let edge = search.clone().find(|&s| active[s - 1] < threshold && active[s] >= threshold)?;
+    let mut pair = [0_u8; 2];
+    for k in 0..16 {
+        let centre = edge as f32 + (k as f32 + 1.5) * bit_len;
+        let window = (centre - 0.25 * bit_len) as usize
+            ..((centre + 0.25 * bit_len) as usize + 1).min(active.len());
+        if window.is_empty() {
+            return None;
+        }
+        let level = active[window.clone()].iter().sum::<f32>() / window.len() as f32;
+        if level > threshold {
+            pair[k / 8] |= 1 << (k % 8);
+        }
+    }
+    Some(pair)
+}
+
+/// Running tally of caption bytes through the channel. A byte survives when the slicer
+/// reads it back unchanged with good parity.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CaptionReport {
+    pub frames: usize,
+    pub bytes_sent: usize,
+    pub bytes_intact: usize,
+    /// Bytes read with bad parity (the decoder would drop them).
+    pub parity_errors: usize,
+    /// Bytes read with good parity but the wrong value (would be shown as garbage).
+    pub undetected_errors: usize,
+    /// Frames where the slicer found no run-in or start bit.
+    pub lines_lost: usize,
+}
+
+impl CaptionReport {
+    pub fn record(&mut self, sent: [u8; 2], read: Option<[u8; 2]>) {
+        self.frames += 1;
+        self.bytes_sent += 2;
+        let Some(read) = read else {
+            self.lines_lost += 1;
+            return;
+        };
+        for (sent, read) in sent.into_iter().zip(read) {
+            if !parity_ok(read) {
+                self.parity_errors += 1;
+            } else if read == sent {
+                self.bytes_intact += 1;
+            } else {
+                self.undetected_errors += 1;
+            }
+        }
+    }
+
+    pub fn survival(&self) -> f32 {
+        self.bytes_intact as f32 / self.bytes_sent.max(1) as f32
+    }
+}
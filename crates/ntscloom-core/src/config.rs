@@ -1,10 +1,26 @@
+use crate::captions::CAPTION_LINE;
+use crate::dsp::filter::FilterPrototype;
+use crate::dsp::{bt601_oetf, linear_to_srgb, srgb_to_linear};
+use crate::vits::VitsSignal;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CompositeConfig {
     pub subcarrier_phase_deg: f32,
     pub burst_amplitude: f32,
     pub chroma_level: f32,
+    /// Whether the encoder matrix (and the decoder's inverse) works on gamma-corrected
+    /// R'G'B' or on linear light.
+    pub signal_domain: SignalDomain,
+    /// Camera transfer function forming R'G'B' in the gamma-encoded domain.
+    pub camera_transfer: CameraTransfer,
+    /// Black level above blanking (7.5 IRE for NTSC-M, 0 for NTSC-J); picture white is 100.
+    pub setup_ire: f32,
+    /// How the encoder keeps picture excursions inside `legal_min_ire..legal_max_ire`.
+    pub legalizer: LegalizerMode,
+    pub legal_max_ire: f32,
+    pub legal_min_ire: f32,
 }
 
 impl Default for CompositeConfig {
@@ -13,17 +29,217 @@ impl Default for CompositeConfig {
             subcarrier_phase_deg: 0.0,
             burst_amplitude: 1.0,
             chroma_level: 1.0,
+            signal_domain: SignalDomain::GammaEncoded,
+            camera_transfer: CameraTransfer::Bt601,
+            setup_ire: 7.5,
+            legalizer: LegalizerMode::Clip,
+            legal_max_ire: 120.0,
+            legal_min_ire: -20.0,
         }
     }
 }
 
+/// Handling of composite excursions (luma plus chroma peak) outside the legal range.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum LegalizerMode {
+    /// Pass everything; saturated yellows peak near 130 IRE.
+    Off,
+    /// Encoder clipper on the modulated waveform: flattens chroma peaks, shifting luma and hue.
+    #[default]
+    Clip,
+    /// Legalizer: reduce chroma saturation per pixel until the excursion fits, keeping hue.
+    ReduceChroma,
+}
+
+/// Domain the encoder forms Y'IQ (or YIQ) in.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum SignalDomain {
+    /// Broadcast practice: the matrix runs on R'G'B' from the camera transfer function, and
+    /// the display transfer function turns the decoded R'G'B' back into light.
+    #[default]
+    GammaEncoded,
+    /// Constant-luminance style encoding straight from linear RGB; no transfer functions.
+    Linear,
+}
+
+/// Camera opto-electronic transfer function (linear light to R'G'B').
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum CameraTransfer {
+    /// BT.601: `4.5 L` below 0.018, `1.099 L^0.45 - 0.099` above.
+    #[default]
+    Bt601,
+    /// sRGB encoding, so the input file's code values go into the matrix unchanged.
+    Srgb,
+}
+
+impl CameraTransfer {
+    pub fn encode(self, linear: f32) -> f32 {
+        match self {
+            CameraTransfer::Bt601 => bt601_oetf(linear),
+            CameraTransfer::Srgb => linear_to_srgb(linear),
+        }
+    }
+}
+
+/// Display electro-optical transfer function (decoded R'G'B' to light).
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum DisplayTransfer {
+    /// CRT power law with gamma 2.2.
+    #[default]
+    Crt22,
+    /// CRT power law with gamma 2.5; darker mid-tones, more contrast.
+    Crt25,
+    /// sRGB decoding; undoes `CameraTransfer::Srgb` exactly.
+    Srgb,
+}
+
+impl DisplayTransfer {
+    pub fn decode(self, value: f32) -> f32 {
+        match self {
+            DisplayTransfer::Crt22 => value.max(0.0).powf(2.2),
+            DisplayTransfer::Crt25 => value.max(0.0).powf(2.5),
+            DisplayTransfer::Srgb => srgb_to_linear(value),
+        }
+    }
+}
+
+/// Macrovision-style copy protection added to the composite by the encoder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CopyProtectionConfig {
+    /// Pseudo-sync / AGC pulse pairs in the vertical blanking interval.
+    pub agc_pulses_enabled: bool,
+    /// First and last VBI line (1-based, up to 21) carrying the pulse train.
+    pub agc_pulse_first_line: u32,
+    pub agc_pulse_last_line: u32,
+    pub agc_pulses_per_line: u32,
+    /// AGC pulse peak level at the top of the amplitude cycle.
+    pub agc_pulse_peak_ire: f32,
+    /// Period of the pulse amplitude sweep; 0 holds the peak level.
+    pub agc_cycle_s: f32,
+    /// Burst phase shift on a repeating group of picture lines.
+    pub colorstripe_enabled: bool,
+    pub colorstripe_lines_on: u32,
+    pub colorstripe_period_lines: u32,
+    pub colorstripe_phase_deg: f32,
+}
+
+impl Default for CopyProtectionConfig {
+    fn default() -> Self {
+        Self {
+            agc_pulses_enabled: false,
+            agc_pulse_first_line: 12,
+            agc_pulse_last_line: 19,
+            agc_pulses_per_line: 6,
+            agc_pulse_peak_ire: 110.0,
+            agc_cycle_s: 2.0,
+            colorstripe_enabled: false,
+            colorstripe_lines_on: 2,
+            colorstripe_period_lines: 17,
+            colorstripe_phase_deg: 90.0,
+        }
+    }
+}
+
+/// SMPTE 12M vertical interval timecode numbered from the pipeline's frame index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VitcConfig {
+    pub enabled: bool,
+    /// VBI lines (1-based, 10-21) carrying the timecode; two non-adjacent lines survive a
+    /// dropout on either.
+    pub lines: Vec<u32>,
+    pub drop_frame: bool,
+    /// Eight 4-bit user groups, lowest group first.
+    pub user_bits: u32,
+}
+
+impl Default for VitcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            lines: vec![14, 16],
+            drop_frame: true,
+            user_bits: 0,
+        }
+    }
+}
+
+/// Vertical interval test signals; a line of 0 leaves that signal out.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VitsConfig {
+    pub enabled: bool,
+    pub multiburst_line: u32,
+    pub staircase_line: u32,
+    pub pulse_bar_line: u32,
+    /// NTC-7 style composite: bar, 2T pulse, 12.5T chroma pulse and modulated staircase.
+    pub composite_line: u32,
+}
+
+impl VitsConfig {
+    pub fn signal(&self, line_number: u32) -> Option<VitsSignal> {
+        [
+            (self.multiburst_line, VitsSignal::Multiburst),
+            (self.staircase_line, VitsSignal::ModulatedStaircase),
+            (self.pulse_bar_line, VitsSignal::PulseAndBar),
+            (self.composite_line, VitsSignal::Composite),
+        ]
+        .into_iter()
+        .find(|&(line, _)| line != 0 && line == line_number)
+        .map(|(_, signal)| signal)
+    }
+}
+
+impl Default for VitsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            multiburst_line: 17,
+            staircase_line: 18,
+            pulse_bar_line: 19,
+            composite_line: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ChannelConfig {
     pub luma_bandwidth_mhz: f32,
     pub chroma_bandwidth_mhz: f32,
+    /// Gibbs ringing: 0 = smooth (Hann) luma band limit, 1 = brick-wall truncated sinc.
     pub luma_ringing: f32,
+    /// Peaking / detail enhancement gain (VCR "picture" control, TV sharpness).
+    pub luma_peaking: f32,
+    pub peaking_frequency_mhz: f32,
+    /// Gaussian noise RMS, band-limited to the luma bandwidth.
     pub luma_noise: f32,
+    /// Band-limited I/Q noise RMS (colour "confetti").
+    pub chroma_noise: f32,
+    /// Horizontal size of chroma noise blobs; 1.0 = 500 kHz noise bandwidth.
+    pub color_noise_texture_scale: f32,
+    /// Y/C crosstalk leakage; only applied while `artifacts.crosstalk_dynamic` is on.
     pub dot_crawl_intensity: f32,
+    /// Analog prototype for the encoder/decoder Y and I/Q lowpasses.
+    pub filter_prototype: FilterPrototype,
+    /// Lowpass order (slope = 6 dB/octave per order).
+    pub filter_order: u8,
+    /// AC coupling time constant that tilts each line (streaks right of bright objects);
+    /// 0 = DC coupled.
+    pub line_coupling_us: f32,
+    /// AC coupling time constant over the field (shadows below bright objects, bounce after
+    /// picture level changes); 0 = DC coupled.
+    pub field_coupling_ms: f32,
+    /// Chroma band delay against luma; positive puts colour to the right of edges.
+    pub yc_delay_ns: f32,
+    /// Differential gain: chroma gain change per IRE of luma above blanking, in percent.
+    pub differential_gain_pct_per_ire: f32,
+    /// Differential phase: subcarrier phase shift per IRE of luma above blanking.
+    pub differential_phase_deg_per_ire: f32,
+    /// Shape of both DG/DP curves over 0..100 IRE: 1 = linear, higher values concentrate
+    /// the error in the highlights.
+    pub differential_curve_exponent: f32,
 }
 
 impl Default for ChannelConfig {
@@ -32,19 +248,75 @@ impl Default for ChannelConfig {
             luma_bandwidth_mhz: 4.2,
             chroma_bandwidth_mhz: 1.5,
             luma_ringing: 0.2,
+            luma_peaking: 0.0,
+            peaking_frequency_mhz: 2.5,
             luma_noise: 0.02,
+            chroma_noise: 0.0,
+            color_noise_texture_scale: 1.0,
             dot_crawl_intensity: 0.3,
+            filter_prototype: FilterPrototype::Butterworth,
+            filter_order: 1,
+            line_coupling_us: 0.0,
+            field_coupling_ms: 0.0,
+            yc_delay_ns: 0.0,
+            differential_gain_pct_per_ire: 0.0,
+            differential_phase_deg_per_ire: 0.0,
+            differential_curve_exponent: 1.0,
         }
     }
 }
 
+/// How the deck handles samples flagged by the RF dropout detector.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub enum DropoutCompensation {
+    /// Replace the dropout with luma from the previous line (1H delay).
+    #[default]
+    Doc,
+    /// No DOC: the limiter pushes lost carrier to peak white.
+    WhiteSparkles,
+    /// No DOC: lost carrier is muted to black.
+    BlackSparkles,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TapeDamageKind {
+    /// Burst of dropouts in a horizontal band at `position`.
+    Crease,
+    /// Brief tracking loss: a noise bar rolls through the frame with horizontal tearing.
+    Wrinkle,
+    /// Damaged tape edge: RF loss and dropouts fading in towards the top of the frame.
+    EdgeDamage,
+}
+
+/// A damage event located on the tape timeline, in seconds from the first rendered frame.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TapeDamageEvent {
+    pub kind: TapeDamageKind,
+    pub start_s: f32,
+    pub duration_s: f32,
+    pub severity: f32,
+    pub position: f32,
+}
+
+impl TapeDamageEvent {
+    pub fn is_active(&self, time_s: f32) -> bool {
+        time_s >= self.start_s && time_s < self.start_s + self.duration_s.max(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TapeConfig {
     pub flutter_rate_hz: f32,
     pub flutter_depth: f32,
     pub tracking_error: f32,
     pub dropout_rate: f32,
     pub head_switch_jitter: f32,
+    /// 1/f tape hiss level relative to peak white; -60 dB and below is off.
+    pub tape_hiss_db: f32,
+    pub dropout_threshold: f32,
+    pub dropout_compensation: DropoutCompensation,
+    pub damage_events: Vec<TapeDamageEvent>,
 }
 
 impl Default for TapeConfig {
@@ -55,11 +327,16 @@ impl Default for TapeConfig {
             tracking_error: 0.1,
             dropout_rate: 0.02,
             head_switch_jitter: 0.05,
+            tape_hiss_db: -60.0,
+            dropout_threshold: 0.5,
+            dropout_compensation: DropoutCompensation::Doc,
+            damage_events: Vec::new(),
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct OutputConfig {
     pub bit_depth: u8,
     pub wet_dry_mix: f32,
@@ -74,40 +351,92 @@ impl Default for OutputConfig {
     }
 }
 
+/// A VBI line claimed by two signals. Captions win line 21, then VITC, then VITS, and any
+/// of them replaces the AGC pulses on its line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VbiConflict {
+    pub line: u32,
+    pub kept: &'static str,
+    pub dropped: &'static str,
+}
+
+impl std::fmt::Display for VbiConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "VBI line {}: {} replaces {}", self.line, self.kept, self.dropped)
+    }
+}
+
+/// Missing fields take their defaults, so presets saved before a setting existed still load.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PipelineConfig {
     pub composite: CompositeConfig,
+    pub copy_protection: CopyProtectionConfig,
+    pub vitc: VitcConfig,
+    pub vits: VitsConfig,
     pub channel: ChannelConfig,
     pub tape: TapeConfig,
     pub artifacts: ArtifactConfig,
     pub demodulation: DemodulationConfig,
+    pub receiver: ReceiverConfig,
+    pub capture: CaptureConfig,
+    pub crt: CrtConfig,
     pub precision: PrecisionConfig,
     pub debug: DebugConfig,
     pub output: OutputConfig,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+impl PipelineConfig {
+    /// VBI lines where one signal silently replaces another; `captions` says whether a
+    /// caption track will be sent on line 21.
+    pub fn vbi_conflicts(&self, captions: bool) -> Vec<VbiConflict> {
+        let protection = &self.copy_protection;
+        let pulse_lines = protection.agc_pulse_first_line..=protection.agc_pulse_last_line;
+        let mut conflicts = Vec::new();
+        for line in 1..=CAPTION_LINE as u32 {
+            let claims = [
+                ("captions", captions && line == CAPTION_LINE as u32),
+                ("VITC", self.vitc.enabled && self.vitc.lines.contains(&line)),
+                ("VITS", self.vits.enabled && self.vits.signal(line).is_some()),
+                ("AGC pulses", protection.agc_pulses_enabled && pulse_lines.contains(&line)),
+            ];
+            let mut claimed = claims.into_iter().filter(|&(_, claimed)| claimed);
+            if let Some((kept, _)) = claimed.next() {
+                conflicts.extend(claimed.map(|(dropped, _)| VbiConflict { line, kept, dropped }));
+            }
+        }
+        conflicts
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub enum DemodulationFilter {
+    #[default]
     Lowpass,
     Box,
     Notch,
     Comb1D,
     Comb2D,
-}
-
-impl Default for DemodulationFilter {
-    fn default() -> Self {
-        Self::Lowpass
-    }
+    /// 3-line adaptive comb with notch/bandpass fallback.
+    AdaptiveComb,
+    /// Motion-adaptive frame comb over the adaptive comb (needs `Pipeline` frame history).
+    Comb3D,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DemodulationConfig {
     pub filter: DemodulationFilter,
     pub box_kernel: usize,
     pub notch_bandwidth_mhz: f32,
     pub notch_depth: f32,
     pub comb_strength: f32,
+    /// Line mismatch (luma + chroma, in signal units) at which the adaptive comb gives up
+    /// combing and falls back to notch separation.
+    pub adaptive_threshold: f32,
+    /// Frame-to-frame mismatch at which the 3D comb treats a pixel as moving.
+    pub motion_threshold: f32,
+    pub axes: DecoderAxes,
 }
 
 impl Default for DemodulationConfig {
@@ -118,11 +447,154 @@ impl Default for DemodulationConfig {
             notch_bandwidth_mhz: 0.6,
             notch_depth: 0.5,
             comb_strength: 0.6,
+            adaptive_threshold: 0.1,
+            motion_threshold: 0.1,
+            axes: DecoderAxes::default(),
+        }
+    }
+}
+
+/// One synchronous demodulator. `angle_deg` is measured from the B-Y axis towards R-Y
+/// (burst sits at 180°), so I = 123°, Q = 33°, B-Y = 0° and R-Y = 90°.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct DemodAxis {
+    pub angle_deg: f32,
+    /// Output gain relative to the nominal axis the matrix expects.
+    pub gain: f32,
+    pub bandwidth_mhz: f32,
+}
+
+/// Which pair of axes the decoder matrix was designed for.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum MatrixBasis {
+    #[default]
+    Iq,
+    /// B-Y / R-Y colour-difference outputs.
+    ColorDifference,
+}
+
+/// The two chroma demodulators of a receiver and the matrix that turns their outputs into
+/// colour. Off-nominal angles and gains are what make consumer sets push reds.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DecoderAxes {
+    pub first: DemodAxis,
+    pub second: DemodAxis,
+    pub basis: MatrixBasis,
+}
+
+impl Default for DecoderAxes {
+    fn default() -> Self {
+        AxisProfile::TextbookIq.axes()
+    }
+}
+
+impl DecoderAxes {
+    /// Unit demodulation reference of each axis as `(cos, sin)` in the I/Q plane.
+    pub fn references(&self) -> [(f32, f32); 2] {
+        [iq_unit(self.first.angle_deg), iq_unit(self.second.angle_deg)]
+    }
+
+    /// Maps unit-gain demodulator outputs to I/Q: the inverse of the nominal axes of
+    /// `basis`, times each demodulator's gain.
+    pub fn matrix(&self) -> [[f32; 2]; 2] {
+        let (n1, n2) = match self.basis {
+            MatrixBasis::Iq => (iq_unit(123.0), iq_unit(33.0)),
+            MatrixBasis::ColorDifference => (iq_unit(0.0), iq_unit(90.0)),
+        };
+        let det = n1.0 * n2.1 - n1.1 * n2.0;
+        let inverse = [[n2.1 / det, -n1.1 / det], [-n2.0 / det, n1.0 / det]];
+        let (g1, g2) = (self.first.gain, self.second.gain);
+        [
+            [inverse[0][0] * g1, inverse[0][1] * g2],
+            [inverse[1][0] * g1, inverse[1][1] * g2],
+        ]
+    }
+
+    /// Transmitted I/Q to decoded I/Q. Identity for the textbook profiles; hue and
+    /// saturation errors otherwise.
+    pub fn transfer(&self) -> [[f32; 2]; 2] {
+        let m = self.matrix();
+        let [r1, r2] = self.references();
+        [
+            [m[0][0] * r1.0 + m[0][1] * r2.0, m[0][0] * r1.1 + m[0][1] * r2.1],
+            [m[1][0] * r1.0 + m[1][1] * r2.0, m[1][0] * r1.1 + m[1][1] * r2.1],
+        ]
+    }
+}
+
+fn iq_unit(angle_deg: f32) -> (f32, f32) {
+    let angle = (123.0 - angle_deg).to_radians();
+    (angle.cos(), angle.sin())
+}
+
+/// Built-in demodulator setups.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AxisProfile {
+    /// I (1.3 MHz) and Q (0.5 MHz), as in the FCC specification.
+    TextbookIq,
+    /// B-Y / R-Y at 90°, both 1.0 MHz.
+    EquibandColorDifference,
+    /// Late consumer chip: R-Y at 100°, slight R-Y gain boost, 0.6 MHz equiband.
+    ConsumerMild,
+    /// Classic red push: R-Y at 105° with about 25% extra R-Y gain.
+    ConsumerRedPush,
+    /// Flesh-tone pull: both axes rotated towards the I axis, compressing hues near skin.
+    ConsumerFleshTone,
+}
+
+impl AxisProfile {
+    pub const ALL: [AxisProfile; 5] = [
+        AxisProfile::TextbookIq,
+        AxisProfile::EquibandColorDifference,
+        AxisProfile::ConsumerMild,
+        AxisProfile::ConsumerRedPush,
+        AxisProfile::ConsumerFleshTone,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AxisProfile::TextbookIq => "Textbook I/Q",
+            AxisProfile::EquibandColorDifference => "Equiband R-Y/B-Y",
+            AxisProfile::ConsumerMild => "Consumer (mild)",
+            AxisProfile::ConsumerRedPush => "Consumer red push",
+            AxisProfile::ConsumerFleshTone => "Consumer flesh-tone",
+        }
+    }
+
+    pub fn axes(self) -> DecoderAxes {
+        let axis = |angle_deg, gain, bandwidth_mhz| DemodAxis {
+            angle_deg,
+            gain,
+            bandwidth_mhz,
+        };
+        let color_difference = |first, second| DecoderAxes {
+            first,
+            second,
+            basis: MatrixBasis::ColorDifference,
+        };
+        match self {
+            AxisProfile::TextbookIq => DecoderAxes {
+                first: axis(123.0, 1.0, 1.3),
+                second: axis(33.0, 1.0, 0.5),
+                basis: MatrixBasis::Iq,
+            },
+            AxisProfile::EquibandColorDifference => {
+                color_difference(axis(0.0, 1.0, 1.0), axis(90.0, 1.0, 1.0))
+            }
+            AxisProfile::ConsumerMild => color_difference(axis(0.0, 1.0, 0.6), axis(100.0, 1.1, 0.6)),
+            AxisProfile::ConsumerRedPush => {
+                color_difference(axis(0.0, 1.0, 0.6), axis(105.0, 1.25, 0.6))
+            }
+            AxisProfile::ConsumerFleshTone => {
+                color_difference(axis(10.0, 0.95, 0.6), axis(112.0, 1.15, 0.6))
+            }
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ArtifactConfig {
     pub head_switch_enabled: bool,
     pub head_switch_height: f32,
@@ -174,6 +646,7 @@ impl Default for ArtifactConfig {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PrecisionConfig {
     pub oversample_factor: u8,
     pub preview_oversample_factor: u8,
@@ -202,21 +675,212 @@ impl Default for PrecisionConfig {
     }
 }
 
+/// DC restoration point of the receiver's keyed input clamp.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum ClampMode {
+    /// No DC restoration; the line keeps whatever offset the channel gave it.
+    #[default]
+    Off,
+    /// Pin the sync tip to -40 IRE; sync amplitude errors move the black level.
+    SyncTip,
+    /// Pin the back porch to blanking.
+    BackPorch,
+}
+
+/// Viewer-side picture controls, applied in the decoder's signal chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReceiverConfig {
+    pub clamp: ClampMode,
+    /// Closed-loop gain control that servoes the measured sync amplitude to `agc_reference_ire`.
+    pub agc_enabled: bool,
+    /// Sync amplitude the AGC aims for; 40 IRE is nominal.
+    pub agc_reference_ire: f32,
+    /// AGC loop time constant. Short loops follow noise and dropouts on the sync tip
+    /// (pumping); long loops lag behind level changes.
+    pub agc_time_constant_ms: f32,
+    /// DC offset added to luma after the contrast amplifier.
+    pub brightness: f32,
+    /// Video amplifier gain ("picture"); scales luma and chroma together.
+    pub contrast: f32,
+    /// Chroma amplifier gain (saturation).
+    pub color: f32,
+    /// Demodulator reference phase shift; positive turns flesh tones towards green.
+    pub tint_deg: f32,
+    /// Luma peaking; negative values soften.
+    pub sharpness: f32,
+    /// Transfer function of the picture tube in the gamma-encoded domain.
+    pub display_transfer: DisplayTransfer,
+    /// Black level the set is aligned for; a mismatch with the encoder's setup lifts or
+    /// crushes blacks.
+    pub setup_ire: f32,
+    /// Automatic chroma control: scales chroma by nominal / measured burst amplitude.
+    pub acc_enabled: bool,
+    pub acc_max_gain: f32,
+    /// Time constant of the burst amplitude detector feeding the ACC and the colour killer.
+    pub burst_detector_ms: f32,
+    /// Switch chroma off when the detected burst falls below `color_killer_off_pct` of
+    /// nominal, and back on only above `color_killer_on_pct`.
+    pub color_killer_enabled: bool,
+    pub color_killer_off_pct: f32,
+    pub color_killer_on_pct: f32,
+}
+
+impl Default for ReceiverConfig {
+    fn default() -> Self {
+        Self {
+            clamp: ClampMode::Off,
+            agc_enabled: false,
+            agc_reference_ire: 40.0,
+            agc_time_constant_ms: 10.0,
+            brightness: 0.0,
+            contrast: 1.0,
+            color: 1.0,
+            tint_deg: 0.0,
+            sharpness: 0.0,
+            display_transfer: DisplayTransfer::Crt22,
+            setup_ire: 7.5,
+            acc_enabled: false,
+            acc_max_gain: 3.0,
+            burst_detector_ms: 1.0,
+            color_killer_enabled: false,
+            color_killer_off_pct: 15.0,
+            color_killer_on_pct: 25.0,
+        }
+    }
+}
+
+/// Capture card digitizer between the clamp and the decoder.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureConfig {
+    pub enabled: bool,
+    /// ADC resolution over the input range.
+    pub bits: u8,
+    /// Input window in IRE; anything outside clips (cheap cards clip superblacks).
+    pub input_min_ire: f32,
+    pub input_max_ire: f32,
+    /// RMS sampling clock jitter.
+    pub clock_jitter_ps: f32,
+    /// Chroma resolution of the captured picture.
+    pub chroma_subsampling: ChromaSubsampling,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bits: 8,
+            input_min_ire: -40.0,
+            input_max_ire: 130.0,
+            clock_jitter_ps: 0.0,
+            chroma_subsampling: ChromaSubsampling::Full444,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum ChromaSubsampling {
+    #[default]
+    Full444,
+    /// One chroma sample per two pixels.
+    Sub422,
+    /// One chroma sample per four pixels (DV NTSC).
+    Sub411,
+}
+
+impl ChromaSubsampling {
+    pub fn factor(self) -> usize {
+        match self {
+            ChromaSubsampling::Full444 => 1,
+            ChromaSubsampling::Sub422 => 2,
+            ChromaSubsampling::Sub411 => 4,
+        }
+    }
+}
+
+/// CRT the picture is watched on, rendered after the decoder at `scale` output pixels per
+/// input pixel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CrtConfig {
+    pub enabled: bool,
+    /// Output pixels per input pixel, both directions.
+    pub scale: u32,
+    /// Beam spot sigma in scanline pitches at black and at full drive; a narrow spot
+    /// leaves dark gaps between the lines.
+    pub spot_dark: f32,
+    pub spot_bright: f32,
+    pub mask: PhosphorMask,
+    /// Phosphor triad pitch in output pixels.
+    pub mask_pitch: f32,
+    /// 0 = no mask visible, 1 = black between phosphors.
+    pub mask_strength: f32,
+    /// Share of the light scattered inside the faceplate glass, and how far (input pixels).
+    pub halation: f32,
+    pub halation_radius: f32,
+    /// Glow around highlights above the bloom threshold, and its radius (input pixels).
+    pub bloom: f32,
+    pub bloom_radius: f32,
+    /// Red, green and blue phosphor decay time constants in frames; 0 = no persistence.
+    pub phosphor_decay_frames: [f32; 3],
+}
+
+impl Default for CrtConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scale: 3,
+            spot_dark: 0.3,
+            spot_bright: 0.55,
+            mask: PhosphorMask::ApertureGrille,
+            mask_pitch: 3.0,
+            mask_strength: 0.3,
+            halation: 0.05,
+            halation_radius: 8.0,
+            bloom: 0.15,
+            bloom_radius: 2.0,
+            phosphor_decay_frames: [0.0; 3],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum PhosphorMask {
+    None,
+    /// Continuous vertical RGB stripes (Trinitron).
+    #[default]
+    ApertureGrille,
+    /// Stripes cut into slots, staggered between neighbouring triads.
+    SlotMask,
+    /// Delta triads of round dots, alternate rows offset by half a triad.
+    ShadowMask,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DebugConfig {
     pub diagnostic_mode: bool,
     pub show_composite: bool,
     pub show_iq: bool,
     pub show_grid: bool,
+    /// Adaptive comb blend map (red = up, blue = down, green = notch fallback, white = frame comb).
+    pub show_comb_blend: bool,
+    /// Paint input pixels whose composite excursion is outside the legal range magenta.
+    pub show_illegal: bool,
+    /// Output the whole 910 x 525 raster instead of the decoded picture.
+    pub raster_view: RasterView,
 }
 
-impl Default for DebugConfig {
-    fn default() -> Self {
-        Self {
-            diagnostic_mode: false,
-            show_composite: false,
-            show_iq: false,
-            show_grid: false,
-        }
-    }
+/// What the pipeline outputs: the active picture, or the full 525-line raster with
+/// blanking, sync, burst and VBI at 910 samples per line.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum RasterView {
+    #[default]
+    Active,
+    /// Composite voltage as greyscale on the 10-bit 4fsc scale (sync 16, blanking 240,
+    /// white 800).
+    RawComposite,
+    /// Every line decoded, as on a monitor switched to underscan.
+    Underscan,
 }
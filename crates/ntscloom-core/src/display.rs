@@ -0,0 +1,279 @@
+//! CRT display stage: beam spot, phosphor mask, halation and bloom on the decoded picture,
+//! rendered in linear light at an upscaled resolution.
+
+use std::f32::consts::TAU;
+
+use crate::config::{CrtConfig, PhosphorMask};
+use crate::dsp::{linear_to_srgb, srgb_to_linear};
+use crate::pipeline::Frame;
+
+/// Linear light above which highlights bloom.
+const BLOOM_THRESHOLD: f32 = 0.6;
+/// The video along a line is band-limited, so the spot never resolves single pixels there.
+const MIN_HORIZONTAL_SPOT: f32 = 0.5;
+/// Gaussians are cut off this many sigmas out.
+const SPOT_EXTENT: f32 = 3.0;
+
+/// Light still stored in the phosphors at the end of the last frame, per output sample,
+/// and the mask layout, whose gains only change with the mask settings and output size.
+#[derive(Default)]
+pub(crate) struct PhosphorState {
+    residual: Vec<f32>,
+    layout: Option<PhosphorLayout>,
+}
+
+impl PhosphorState {
+    fn layout(&mut self, crt: &CrtConfig, width: usize, height: usize) -> &PhosphorLayout {
+        if !self.layout.as_ref().is_some_and(|layout| layout.fits(crt, width, height)) {
+            self.layout = None;
+        }
+        self.layout.get_or_insert_with(|| PhosphorLayout::new(crt, width, height))
+    }
+}
+
+/// Draws `frame` (sRGB code values) on the CRT described by `crt`; the result is
+/// `crt.scale` times larger in both directions. `phosphor` carries the afterglow into the
+/// next frame.
+pub(crate) fn render_crt(frame: &Frame, crt: &CrtConfig, phosphor: &mut PhosphorState) -> Frame {
+    let scale = crt.scale.max(1) as usize;
+    let linear: Vec<f32> = frame.data.iter().map(|&value| srgb_to_linear(value)).collect();
+    let beam = scan_beam(&linear, frame.width, frame.height, scale, crt);
+    let glow = glow(&linear, frame.width, frame.height, crt);
+
+    let mut out = Frame::new(frame.width * scale, frame.height * scale);
+    let mask = phosphor.layout(crt, out.width, out.height);
+    let direct = 1.0 - crt.halation.clamp(0.0, 1.0);
+    for (n, pixel) in out.data.chunks_exact_mut(3).enumerate() {
+        let (x, y) = (n % (frame.width * scale), n / (frame.width * scale));
+        let u = (x as f32 + 0.5) / scale as f32 - 0.5;
+        let v = (y as f32 + 0.5) / scale as f32 - 0.5;
+        for (channel, value) in pixel.iter_mut().enumerate() {
+            let lit = direct * beam[n * 3 + channel] * mask.factor(x, y, channel);
+            *value = lit + bilinear(&glow, frame.width, frame.height, u, v, channel);
+        }
+    }
+    persist(&mut out, scale, crt, phosphor);
+    for value in out.data.iter_mut() {
+        *value = linear_to_srgb(*value).clamp(0.0, 1.0);
+    }
+    out
+}
+
+/// Phosphor persistence as seen by a viewer integrating whole frames. Each line is excited
+/// once a frame, the second field's half a frame after the first's, and its light decays
+/// with the channel's time constant: the part emitted after the frame ends shows in the
+/// next ones. The screen starts out as if it had been showing the first frame.
+fn persist(light: &mut Frame, scale: usize, crt: &CrtConfig, phosphor: &mut PhosphorState) {
+    let fresh = phosphor.residual.len() != light.data.len();
+    if fresh {
+        phosphor.residual = vec![0.0; light.data.len()];
+    }
+    let row_len = light.width * 3;
+    for (n, (value, residual)) in light.data.iter_mut().zip(&mut phosphor.residual).enumerate() {
+        let tau = crt.phosphor_decay_frames[n % 3];
+        if tau <= 0.0 {
+            *residual = 0.0;
+            continue;
+        }
+        let line = ((n / row_len) as f32 + 0.5) / scale as f32 - 0.5;
+        let field_delay = if (line.round() as usize).is_multiple_of(2) { 0.0 } else { 0.5 };
+        let frame_decay = (-1.0 / tau).exp();
+        let spill = (-(1.0 - field_delay) / tau).exp();
+        let stored = if fresh { *value * spill / (1.0 - frame_decay) } else { *residual };
+        *residual = stored * frame_decay + *value * spill;
+        *value = stored * (1.0 - frame_decay) + *value * (1.0 - spill);
+    }
+}
+
+/// Beam spot sigma, in line pitches, for a gun driven to `level`: the spot grows with
+/// beam current.
+fn spot_sigma(level: f32, crt: &CrtConfig) -> f32 {
+    let sigma = crt.spot_dark + (crt.spot_bright - crt.spot_dark) * level.clamp(0.0, 1.0);
+    sigma.max(0.05)
+}
+
+fn gaussian(offset: f32, sigma: f32) -> f32 {
+    (-0.5 * (offset / sigma).powi(2)).exp() / (sigma * TAU.sqrt())
+}
+
+/// Light from each gun's spot: every pixel deposits a Gaussian of its own brightness-
+/// dependent width, along the line and then across the lines.
+fn scan_beam(
+    linear: &[f32],
+    width: usize,
+    height: usize,
+    scale: usize,
+    crt: &CrtConfig,
+) -> Vec<f32> {
+    let out_width = width * scale;
+    let widest = crt.spot_dark.max(crt.spot_bright).max(MIN_HORIZONTAL_SPOT);
+    let reach = (SPOT_EXTENT * widest).ceil();
+    let window = |centre: f32, len: usize| {
+        (centre - reach).max(0.0) as usize..((centre + reach) as usize + 1).min(len)
+    };
+
+    let mut lines = vec![0.0; height * out_width * 3];
+    for y in 0..height {
+        for x in 0..out_width {
+            let u = (x as f32 + 0.5) / scale as f32 - 0.5;
+            for channel in 0..3 {
+                lines[(y * out_width + x) * 3 + channel] = window(u, width)
+                    .map(|source| {
+                        let level = linear[(y * width + source) * 3 + channel];
+                        let sigma = spot_sigma(level, crt).max(MIN_HORIZONTAL_SPOT);
+                        level * gaussian(u - source as f32, sigma)
+                    })
+                    .sum();
+            }
+        }
+    }
+
+    let mut beam = vec![0.0; height * scale * out_width * 3];
+    for y in 0..height * scale {
+        let v = (y as f32 + 0.5) / scale as f32 - 0.5;
+        for x in 0..out_width {
+            for channel in 0..3 {
+                beam[(y * out_width + x) * 3 + channel] = window(v, height)
+                    .map(|line| {
+                        let level = lines[(line * out_width + x) * 3 + channel];
+                        level * gaussian(v - line as f32, spot_sigma(level, crt))
+                    })
+                    .sum();
+            }
+        }
+    }
+    beam
+}
+
+/// Light that misses the spot, at input resolution: halation scatters a share of all
+/// light inside the faceplate, bloom spreads highlights above the threshold.
+fn glow(linear: &[f32], width: usize, height: usize, crt: &CrtConfig) -> Vec<f32> {
+    let halo = gaussian_blur(linear, width, height, crt.halation_radius);
+    let highlights: Vec<f32> =
+        linear.iter().map(|&value| (value - BLOOM_THRESHOLD).max(0.0)).collect();
+    let bloom = gaussian_blur(&highlights, width, height, crt.bloom_radius);
+    halo.iter()
+        .zip(&bloom)
+        .map(|(&halo, &bloom)| crt.halation.clamp(0.0, 1.0) * halo + crt.bloom * bloom)
+        .collect()
+}
+
+/// Separable Gaussian blur of interleaved RGB, renormalized at the edges.
+fn gaussian_blur(data: &[f32], width: usize, height: usize, sigma: f32) -> Vec<f32> {
+    if sigma <= 0.0 {
+        return data.to_vec();
+    }
+    let radius = (SPOT_EXTENT * sigma).ceil() as isize;
+    let taps: Vec<f32> = (-radius..=radius).map(|k| gaussian(k as f32, sigma)).collect();
+    let pass = |input: &[f32], len: usize, index: &dyn Fn(usize, usize) -> usize| {
+        let mut output = vec![0.0; input.len()];
+        let lanes = input.len() / 3 / len;
+        for lane in 0..lanes {
+            for at in 0..len {
+                let (mut sum, mut weight) = ([0.0; 3], 0.0);
+                for (k, tap) in (-radius..=radius).zip(&taps) {
+                    let source = at as isize + k;
+                    if source < 0 || source >= len as isize {
+                        continue;
+                    }
+                    let idx = index(lane, source as usize) * 3;
+                    for (channel, sum) in sum.iter_mut().enumerate() {
+                        *sum += tap * input[idx + channel];
+                    }
+                    weight += tap;
+                }
+                let idx = index(lane, at) * 3;
+                for (channel, sum) in sum.iter().enumerate() {
+                    output[idx + channel] = sum / weight;
+                }
+            }
+        }
+        output
+    };
+    let rows = pass(data, width, &|y, x| y * width + x);
+    pass(&rows, height, &|x, y| y * width + x)
+}
+
+fn bilinear(data: &[f32], width: usize, height: usize, u: f32, v: f32, channel: usize) -> f32 {
+    let u = u.clamp(0.0, (width - 1) as f32);
+    let v = v.clamp(0.0, (height - 1) as f32);
+    let (x0, y0) = (u as usize, v as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (u - x0 as f32, v - y0 as f32);
+    let at = |x: usize, y: usize| data[(y * width + x) * 3 + channel];
+    let top = at(x0, y0) + fx * (at(x1, y0) - at(x0, y0));
+    let bottom = at(x0, y1) + fx * (at(x1, y1) - at(x0, y1));
+    top + fy * (bottom - top)
+}
+
+/// Phosphor layout on the output grid. The mask is scaled so each channel keeps its
+/// average brightness, as the tube is driven harder to make up for the mask.
+struct PhosphorLayout {
+    mask: PhosphorMask,
+    pitch: f32,
+    strength: f32,
+    size: (usize, usize),
+    gain: [f32; 3],
+}
+
+impl PhosphorLayout {
+    fn new(crt: &CrtConfig, width: usize, height: usize) -> Self {
+        let mut layout = Self {
+            mask: crt.mask,
+            pitch: crt.mask_pitch.max(1.0),
+            strength: crt.mask_strength.clamp(0.0, 1.0),
+            size: (width, height),
+            gain: [1.0; 3],
+        };
+        let mut sum = [0.0_f32; 3];
+        for y in 0..height {
+            for x in 0..width {
+                for (channel, sum) in sum.iter_mut().enumerate() {
+                    *sum += layout.factor(x, y, channel);
+                }
+            }
+        }
+        let pixels = (width * height).max(1) as f32;
+        layout.gain = sum.map(|sum| if sum > 0.0 { pixels / sum } else { 1.0 });
+        layout
+    }
+
+    fn fits(&self, crt: &CrtConfig, width: usize, height: usize) -> bool {
+        self.mask == crt.mask
+            && self.pitch == crt.mask_pitch.max(1.0)
+            && self.strength == crt.mask_strength.clamp(0.0, 1.0)
+            && self.size == (width, height)
+    }
+
+    fn factor(&self, x: usize, y: usize, channel: usize) -> f32 {
+        let lit = self.is_lit(x as f32 + 0.5, y as f32 + 0.5, channel);
+        self.gain[channel] * if lit { 1.0 } else { 1.0 - self.strength }
+    }
+
+    fn is_lit(&self, x: f32, y: f32, channel: usize) -> bool {
+        let triad = x / self.pitch;
+        let stripe = |triad: f32| ((triad.fract() * 3.0) as usize).min(2) == channel;
+        match self.mask {
+            PhosphorMask::None => true,
+            PhosphorMask::ApertureGrille => stripe(triad),
+            PhosphorMask::SlotMask => {
+                // Slots two pitches tall, bridged by a one-pixel rib, offset by half a slot
+                // in every other triad.
+                let slot = 2.0 * self.pitch;
+                let offset = if (triad as usize).is_multiple_of(2) { 0.0 } else { self.pitch };
+                stripe(triad) && (y + offset) % slot < slot - 1.0
+            }
+            PhosphorMask::ShadowMask => {
+                // Dots on a triangular lattice: rows sqrt(3)/2 pitch apart, alternate rows
+                // shifted half a triad.
+                let row_height = 0.5 * 3.0_f32.sqrt() * self.pitch;
+                let row = (y / row_height) as usize;
+                let shifted = triad + if row.is_multiple_of(2) { 0.0 } else { 0.5 };
+                let dot = (shifted * 3.0).floor();
+                let dx = (shifted * 3.0 - dot - 0.5) * 2.0;
+                let dy = (y / row_height - row as f32 - 0.5) * 2.0;
+                dot as usize % 3 == channel && dx * dx + dy * dy <= 1.0
+            }
+        }
+    }
+}
@@ -1,5 +1,12 @@
 use std::f32::consts::PI;
 
+pub mod filter;
+pub mod noise;
+
+pub const SUBCARRIER_HZ: f32 = 3_579_545.0;
+pub const SUBCARRIER_CYCLES_PER_LINE: f32 = 227.5;
+pub const LINES_PER_FRAME: u64 = 525;
+
 #[derive(Debug, Clone, Copy)]
 pub struct Yiq {
     pub y: f32,
@@ -49,6 +56,10 @@ impl PhasePll {
         self.phase += delta * self.lock_slew + noise * phase_noise;
         self.phase
     }
+
+    pub fn rebase(&mut self, delta: f32) {
+        self.phase += delta;
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -72,6 +83,15 @@ impl SimpleRng {
     }
 }
 
+/// Subcarrier phase at the start of a line. With 227.5 cycles per line the phase
+/// inverts on every line, and with an odd 525 lines per frame it also inverts on
+/// every frame, which is what makes dot crawl move.
+pub fn line_start_phase(frame_index: u64, line: usize) -> f32 {
+    let absolute_line = frame_index * LINES_PER_FRAME + line as u64;
+    let half_cycles = absolute_line * (SUBCARRIER_CYCLES_PER_LINE * 2.0) as u64;
+    (half_cycles % 2) as f32 * PI
+}
+
 pub fn soft_clip(value: f32, strength: f32) -> f32 {
     let k = strength.max(0.0);
     (value * (1.0 + k)) / (1.0 + k * value.abs())
@@ -93,6 +113,15 @@ pub fn linear_to_srgb(value: f32) -> f32 {
     }
 }
 
+/// BT.601 camera transfer function: linear light to gamma-corrected signal.
+pub fn bt601_oetf(value: f32) -> f32 {
+    if value < 0.018 {
+        value * 4.5
+    } else {
+        1.099 * value.powf(0.45) - 0.099
+    }
+}
+
 pub fn rgb_to_yiq(r: f32, g: f32, b: f32) -> Yiq {
     let y = 0.299 * r + 0.587 * g + 0.114 * b;
     let i = 0.596 * r - 0.274 * g - 0.322 * b;
@@ -0,0 +1,488 @@
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Frequency-domain queries shared by every filter in this module. Frequencies are in Hz,
+/// group delay is in seconds.
+pub trait FrequencyResponse {
+    fn sample_rate_hz(&self) -> f32;
+
+    /// Complex response at `freq_hz` as `(re, im)`.
+    fn response(&self, freq_hz: f32) -> (f64, f64);
+
+    /// Group delay in samples at `freq_hz`.
+    fn group_delay_samples(&self, freq_hz: f32) -> f64;
+
+    fn magnitude(&self, freq_hz: f32) -> f32 {
+        let (re, im) = self.response(freq_hz);
+        (re * re + im * im).sqrt() as f32
+    }
+
+    fn magnitude_db(&self, freq_hz: f32) -> f32 {
+        20.0 * self.magnitude(freq_hz).max(1e-12).log10()
+    }
+
+    fn group_delay_s(&self, freq_hz: f32) -> f32 {
+        (self.group_delay_samples(freq_hz) / self.sample_rate_hz().max(1.0) as f64) as f32
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum FilterPrototype {
+    #[default]
+    Butterworth,
+    Bessel,
+    Gaussian,
+}
+
+/// A lowpass specified by prototype, order and -3 dB cutoff.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FilterSpec {
+    pub prototype: FilterPrototype,
+    pub order: u8,
+    pub cutoff_hz: f32,
+}
+
+impl FilterSpec {
+    pub fn new(prototype: FilterPrototype, order: u8, cutoff_hz: f32) -> Self {
+        Self {
+            prototype,
+            order,
+            cutoff_hz,
+        }
+    }
+
+    pub fn design(&self, sample_rate_hz: f32) -> BiquadCascade {
+        BiquadCascade::lowpass(self, sample_rate_hz)
+    }
+}
+
+/// Symmetric FIR. `apply` runs it zero-phase over a whole line, so responses are reported
+/// relative to the centre tap.
+#[derive(Debug, Clone)]
+pub struct FirFilter {
+    pub taps: Vec<f32>,
+    pub sample_rate_hz: f32,
+}
+
+impl FirFilter {
+    pub fn identity(sample_rate_hz: f32) -> Self {
+        Self {
+            taps: vec![1.0],
+            sample_rate_hz,
+        }
+    }
+
+    /// Windowed-sinc lowpass. `rectangular` blends the Hann window towards a plain
+    /// truncated sinc (1.0), which is the brick-wall design that rings with Gibbs overshoot.
+    pub fn windowed_sinc_lowpass(cutoff_hz: f32, sample_rate_hz: f32, rectangular: f32) -> Self {
+        let fc = normalized(cutoff_hz, sample_rate_hz);
+        let half = half_length(fc);
+        let blend = rectangular.clamp(0.0, 1.0);
+        let mut taps: Vec<f32> = (0..=2 * half)
+            .map(|n| {
+                let x = n as f32 - half as f32;
+                let window = hann(x, half) * (1.0 - blend) + blend;
+                sinc_lowpass(fc, x) * window
+            })
+            .collect();
+        normalize_dc(&mut taps);
+        Self {
+            taps,
+            sample_rate_hz,
+        }
+    }
+
+    /// Hann-windowed bandpass between `low_hz` and `high_hz`, unity gain at band centre.
+    pub fn windowed_sinc_bandpass(low_hz: f32, high_hz: f32, sample_rate_hz: f32) -> Self {
+        let low = normalized(low_hz.min(high_hz), sample_rate_hz);
+        let high = normalized(high_hz.max(low_hz), sample_rate_hz);
+        let half = half_length((high - low).max(1e-4));
+        let taps = (0..=2 * half)
+            .map(|n| {
+                let x = n as f32 - half as f32;
+                (sinc_lowpass(high, x) - sinc_lowpass(low, x)) * hann(x, half)
+            })
+            .collect();
+        let mut filter = Self {
+            taps,
+            sample_rate_hz,
+        };
+        let gain = filter.magnitude(0.5 * (low_hz + high_hz));
+        if gain > 1e-6 {
+            for tap in filter.taps.iter_mut() {
+                *tap /= gain;
+            }
+        }
+        filter
+    }
+
+    /// Hann-windowed band-stop centred on `center_hz`, unity gain at DC.
+    pub fn windowed_sinc_notch(center_hz: f32, bandwidth_hz: f32, sample_rate_hz: f32) -> Self {
+        let half_band = 0.5 * bandwidth_hz.max(1.0);
+        let bandpass = Self::windowed_sinc_bandpass(
+            (center_hz - half_band).max(1.0),
+            center_hz + half_band,
+            sample_rate_hz,
+        );
+        let center = bandpass.taps.len() / 2;
+        let taps = bandpass
+            .taps
+            .iter()
+            .enumerate()
+            .map(|(n, tap)| if n == center { 1.0 - tap } else { -tap })
+            .collect();
+        Self {
+            taps,
+            sample_rate_hz,
+        }
+    }
+
+    pub fn convolve(&self, other: &FirFilter) -> Self {
+        let mut taps = vec![0.0; self.taps.len() + other.taps.len() - 1];
+        for (i, a) in self.taps.iter().enumerate() {
+            for (j, b) in other.taps.iter().enumerate() {
+                taps[i + j] += a * b;
+            }
+        }
+        Self {
+            taps,
+            sample_rate_hz: self.sample_rate_hz,
+        }
+    }
+
+    pub fn apply(&self, input: &[f32]) -> Vec<f32> {
+        let half = (self.taps.len() / 2) as isize;
+        let last = input.len() as isize - 1;
+        (0..input.len() as isize)
+            .map(|n| {
+                self.taps
+                    .iter()
+                    .enumerate()
+                    .map(|(k, tap)| tap * input[(n + k as isize - half).clamp(0, last) as usize])
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn centered_coefficients(&self) -> impl Iterator<Item = (f64, f64)> + Clone + '_ {
+        let half = (self.taps.len() / 2) as f64;
+        self.taps
+            .iter()
+            .enumerate()
+            .map(move |(n, tap)| (n as f64 - half, *tap as f64))
+    }
+}
+
+impl FrequencyResponse for FirFilter {
+    fn sample_rate_hz(&self) -> f32 {
+        self.sample_rate_hz
+    }
+
+    fn response(&self, freq_hz: f32) -> (f64, f64) {
+        let w = omega(freq_hz, self.sample_rate_hz);
+        self.centered_coefficients()
+            .fold((0.0, 0.0), |(re, im), (n, c)| {
+                (re + c * (w * n).cos(), im - c * (w * n).sin())
+            })
+    }
+
+    fn group_delay_samples(&self, freq_hz: f32) -> f64 {
+        let w = omega(freq_hz, self.sample_rate_hz);
+        polynomial_group_delay(self.centered_coefficients(), w)
+    }
+}
+
+/// Second-order section, transposed direct form II. First-order sections use `b2 = a2 = 0`.
+#[derive(Debug, Clone, Copy)]
+pub struct Biquad {
+    pub b0: f32,
+    pub b1: f32,
+    pub b2: f32,
+    pub a1: f32,
+    pub a2: f32,
+    z1: f32,
+    z2: f32,
+}
+
+impl Biquad {
+    pub fn new(b0: f32, b1: f32, b2: f32, a1: f32, a2: f32) -> Self {
+        Self {
+            b0,
+            b1,
+            b2,
+            a1,
+            a2,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        let output = self.b0 * input + self.z1;
+        self.z1 = self.b1 * input - self.a1 * output + self.z2;
+        self.z2 = self.b2 * input - self.a2 * output;
+        output
+    }
+
+    fn numerator(&self) -> [(f64, f64); 3] {
+        [
+            (0.0, self.b0 as f64),
+            (1.0, self.b1 as f64),
+            (2.0, self.b2 as f64),
+        ]
+    }
+
+    fn denominator(&self) -> [(f64, f64); 3] {
+        [(0.0, 1.0), (1.0, self.a1 as f64), (2.0, self.a2 as f64)]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BiquadCascade {
+    pub sections: Vec<Biquad>,
+    pub sample_rate_hz: f32,
+}
+
+impl BiquadCascade {
+    /// Lowpass from an analog prototype via the bilinear transform with cutoff prewarping.
+    pub fn lowpass(spec: &FilterSpec, sample_rate_hz: f32) -> Self {
+        let fs = sample_rate_hz.max(1.0) as f64;
+        let fc = (spec.cutoff_hz as f64).clamp(1.0, 0.49 * fs);
+        let k = 2.0 * fs;
+        let warped = k * (PI * fc / fs).tan();
+        let sections = prototype_poles(spec.prototype, spec.order.clamp(1, MAX_ORDER))
+            .into_iter()
+            .filter(|pole| pole.1 > -1e-9)
+            .map(|(re, im)| {
+                let (re, im) = (re * warped, im * warped);
+                if im.abs() < 1e-9 {
+                    let sigma = -re;
+                    let d0 = k + sigma;
+                    Biquad::new(
+                        (sigma / d0) as f32,
+                        (sigma / d0) as f32,
+                        0.0,
+                        ((sigma - k) / d0) as f32,
+                        0.0,
+                    )
+                } else {
+                    let a1 = -2.0 * re;
+                    let a0 = re * re + im * im;
+                    let d0 = k * k + a1 * k + a0;
+                    Biquad::new(
+                        (a0 / d0) as f32,
+                        (2.0 * a0 / d0) as f32,
+                        (a0 / d0) as f32,
+                        ((2.0 * a0 - 2.0 * k * k) / d0) as f32,
+                        ((k * k - a1 * k + a0) / d0) as f32,
+                    )
+                }
+            })
+            .collect();
+        Self {
+            sections,
+            sample_rate_hz,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for section in self.sections.iter_mut() {
+            section.reset();
+        }
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        self.sections
+            .iter_mut()
+            .fold(input, |value, section| section.process(value))
+    }
+}
+
+impl FrequencyResponse for BiquadCascade {
+    fn sample_rate_hz(&self) -> f32 {
+        self.sample_rate_hz
+    }
+
+    fn response(&self, freq_hz: f32) -> (f64, f64) {
+        let w = omega(freq_hz, self.sample_rate_hz);
+        self.sections.iter().fold((1.0, 0.0), |acc, section| {
+            let num = evaluate(section.numerator().into_iter(), w);
+            let den = evaluate(section.denominator().into_iter(), w);
+            complex_mul(acc, complex_div(num, den))
+        })
+    }
+
+    fn group_delay_samples(&self, freq_hz: f32) -> f64 {
+        let w = omega(freq_hz, self.sample_rate_hz);
+        self.sections
+            .iter()
+            .map(|section| {
+                polynomial_group_delay(section.numerator().into_iter(), w)
+                    - polynomial_group_delay(section.denominator().into_iter(), w)
+            })
+            .sum()
+    }
+}
+
+pub const MAX_ORDER: u8 = 8;
+
+/// Analog lowpass poles normalized to a -3 dB cutoff of 1 rad/s.
+pub fn prototype_poles(prototype: FilterPrototype, order: u8) -> Vec<(f64, f64)> {
+    let n = order.clamp(1, MAX_ORDER) as usize;
+    let poles = match prototype {
+        FilterPrototype::Butterworth => (0..n)
+            .map(|k| {
+                let theta = PI * (2 * k + n + 1) as f64 / (2 * n) as f64;
+                (theta.cos(), theta.sin())
+            })
+            .collect(),
+        FilterPrototype::Bessel => {
+            // Reverse Bessel polynomial: a_k = (2n - k)! / (2^(n-k) k! (n - k)!).
+            let coefficients: Vec<f64> = (0..=n)
+                .map(|k| {
+                    factorial(2 * n - k)
+                        / (2f64.powi((n - k) as i32) * factorial(k) * factorial(n - k))
+                })
+                .collect();
+            polynomial_roots(&coefficients)
+        }
+        FilterPrototype::Gaussian => {
+            // |H(jw)|^2 = 1 / sum_k w^(2k) / k!, a truncated Taylor series of exp(w^2).
+            // Substituting w^2 = -s^2 gives a polynomial in s whose left-half roots are the poles.
+            let mut coefficients = vec![0.0; 2 * n + 1];
+            for k in 0..=n {
+                let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+                coefficients[2 * k] = sign / factorial(k);
+            }
+            polynomial_roots(&coefficients)
+                .into_iter()
+                .filter(|pole| pole.0 < 0.0)
+                .collect()
+        }
+    };
+    normalize_cutoff(poles)
+}
+
+fn normalize_cutoff(poles: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    let magnitude_sq = |w: f64| {
+        poles.iter().fold(1.0, |acc, &(re, im)| {
+            let mag_sq = re * re + im * im;
+            let dist_sq = re * re + (w - im) * (w - im);
+            acc * mag_sq / dist_sq
+        })
+    };
+    let (mut low, mut high) = (1e-3_f64, 1e3_f64);
+    for _ in 0..100 {
+        let mid = (low * high).sqrt();
+        if magnitude_sq(mid) > 0.5 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    let scale = 1.0 / (low * high).sqrt();
+    poles
+        .into_iter()
+        .map(|(re, im)| (re * scale, im * scale))
+        .collect()
+}
+
+/// Durand-Kerner root finder. `coefficients[k]` multiplies `s^k`.
+fn polynomial_roots(coefficients: &[f64]) -> Vec<(f64, f64)> {
+    let degree = coefficients.len() - 1;
+    let lead = coefficients[degree];
+    let monic: Vec<f64> = coefficients.iter().map(|c| c / lead).collect();
+    let eval = |z: (f64, f64)| {
+        monic.iter().rev().fold((0.0, 0.0), |acc, &c| {
+            let (re, im) = complex_mul(acc, z);
+            (re + c, im)
+        })
+    };
+    let mut roots: Vec<(f64, f64)> = (0..degree)
+        .map(|k| {
+            let angle = 2.0 * PI * k as f64 / degree as f64 + 0.4;
+            (0.9 * angle.cos(), 0.9 * angle.sin())
+        })
+        .collect();
+    for _ in 0..500 {
+        for i in 0..degree {
+            let mut den = (1.0, 0.0);
+            for j in 0..degree {
+                if i != j {
+                    den = complex_mul(den, (roots[i].0 - roots[j].0, roots[i].1 - roots[j].1));
+                }
+            }
+            let step = complex_div(eval(roots[i]), den);
+            roots[i] = (roots[i].0 - step.0, roots[i].1 - step.1);
+        }
+    }
+    roots
+        .into_iter()
+        .map(|(re, im)| (re, if im.abs() < 1e-9 { 0.0 } else { im }))
+        .collect()
+}
+
+fn factorial(n: usize) -> f64 {
+    (1..=n).fold(1.0, |acc, k| acc * k as f64)
+}
+
+fn normalized(freq_hz: f32, sample_rate_hz: f32) -> f32 {
+    (freq_hz / sample_rate_hz.max(1.0)).clamp(1e-4, 0.5)
+}
+
+fn half_length(normalized_width: f32) -> usize {
+    ((4.0 / normalized_width) as usize).clamp(4, 128)
+}
+
+fn sinc_lowpass(fc: f32, x: f32) -> f32 {
+    if x == 0.0 {
+        2.0 * fc
+    } else {
+        (2.0 * std::f32::consts::PI * fc * x).sin() / (std::f32::consts::PI * x)
+    }
+}
+
+fn hann(x: f32, half: usize) -> f32 {
+    0.5 + 0.5 * (std::f32::consts::PI * x / (half as f32 + 1.0)).cos()
+}
+
+fn normalize_dc(taps: &mut [f32]) {
+    let sum: f32 = taps.iter().sum();
+    if sum.abs() > 1e-9 {
+        for tap in taps.iter_mut() {
+            *tap /= sum;
+        }
+    }
+}
+
+fn omega(freq_hz: f32, sample_rate_hz: f32) -> f64 {
+    2.0 * PI * freq_hz as f64 / sample_rate_hz.max(1.0) as f64
+}
+
+/// Evaluates `sum c * e^(-j w n)` over `(n, c)` pairs.
+fn evaluate(coefficients: impl Iterator<Item = (f64, f64)>, w: f64) -> (f64, f64) {
+    coefficients.fold((0.0, 0.0), |(re, im), (n, c)| {
+        (re + c * (w * n).cos(), im - c * (w * n).sin())
+    })
+}
+
+/// Group delay of `sum c * z^-n`: `Re(sum n c e^(-j w n) / sum c e^(-j w n))`.
+fn polynomial_group_delay(coefficients: impl Iterator<Item = (f64, f64)> + Clone, w: f64) -> f64 {
+    let weighted = evaluate(coefficients.clone().map(|(n, c)| (n, n * c)), w);
+    let plain = evaluate(coefficients, w);
+    complex_div(weighted, plain).0
+}
+
+fn complex_mul(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+fn complex_div(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    let den = (b.0 * b.0 + b.1 * b.1).max(1e-300);
+    ((a.0 * b.0 + a.1 * b.1) / den, (a.1 * b.0 - a.0 * b.1) / den)
+}
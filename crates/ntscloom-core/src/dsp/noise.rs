@@ -0,0 +1,92 @@
+use super::SimpleRng;
+use std::f32::consts::PI;
+
+/// Unit-variance Gaussian white noise (Box-Muller over `SimpleRng`).
+#[derive(Debug, Clone)]
+pub struct GaussianNoise {
+    rng: SimpleRng,
+    spare: Option<f32>,
+}
+
+impl GaussianNoise {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            rng: SimpleRng::new(seed),
+            spare: None,
+        }
+    }
+
+    pub fn next_sample(&mut self) -> f32 {
+        if let Some(value) = self.spare.take() {
+            return value;
+        }
+        let u1 = self.rng.next_f32().max(1e-7);
+        let u2 = self.rng.next_f32();
+        let radius = (-2.0 * u1.ln()).sqrt();
+        let angle = 2.0 * PI * u2;
+        self.spare = Some(radius * angle.sin());
+        radius * angle.cos()
+    }
+}
+
+/// Approximately unit-variance 1/f noise (Kellett's three-pole filter over white noise).
+#[derive(Debug, Clone)]
+pub struct PinkNoise {
+    white: GaussianNoise,
+    b0: f32,
+    b1: f32,
+    b2: f32,
+}
+
+impl PinkNoise {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            white: GaussianNoise::new(seed),
+            b0: 0.0,
+            b1: 0.0,
+            b2: 0.0,
+        }
+    }
+
+    pub fn next_sample(&mut self) -> f32 {
+        let white = self.white.next_sample();
+        self.b0 = 0.99765 * self.b0 + white * 0.099_046;
+        self.b1 = 0.96300 * self.b1 + white * 0.296_516_4;
+        self.b2 = 0.57000 * self.b2 + white * 1.052_691_3;
+        (self.b0 + self.b1 + self.b2 + white * 0.1848) * PINK_GAIN
+    }
+}
+
+const PINK_GAIN: f32 = 0.344;
+
+/// Gaussian noise through a one-pole lowpass, rescaled back to unit variance.
+#[derive(Debug, Clone)]
+pub struct BandLimitedNoise {
+    white: GaussianNoise,
+    state: f32,
+    alpha: f32,
+    gain: f32,
+}
+
+impl BandLimitedNoise {
+    pub fn new(bandwidth_hz: f32, sample_rate_hz: f32, seed: u32) -> Self {
+        let rc = 1.0 / (2.0 * PI * bandwidth_hz.max(1.0));
+        let dt = 1.0 / sample_rate_hz.max(1.0);
+        let alpha = dt / (rc + dt);
+        Self {
+            white: GaussianNoise::new(seed),
+            state: 0.0,
+            alpha,
+            gain: ((2.0 - alpha) / alpha).sqrt(),
+        }
+    }
+
+    pub fn next_sample(&mut self) -> f32 {
+        self.state += self.alpha * (self.white.next_sample() - self.state);
+        self.state * self.gain
+    }
+}
+
+pub fn db_to_amplitude(db: f32) -> f32 {
+    10.0_f32.powf(db / 20.0)
+}
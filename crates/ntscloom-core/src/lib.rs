@@ -1,11 +1,25 @@
+pub mod captions;
 pub mod config;
+pub mod display;
 pub mod dsp;
 pub mod pipeline;
+pub mod timecode;
+pub mod vitc;
+pub mod vits;
 
+pub use captions::{CaptionError, CaptionReport, CaptionTrack};
 pub use config::{
-    ArtifactConfig, ChannelConfig, CompositeConfig, DebugConfig, DemodulationConfig,
-    DemodulationFilter, OutputConfig, PipelineConfig, PrecisionConfig, TapeConfig,
+    ArtifactConfig, AxisProfile, CameraTransfer, CaptureConfig, ChannelConfig, ChromaSubsampling,
+    ClampMode, CompositeConfig, CopyProtectionConfig, CrtConfig, DebugConfig, DecoderAxes,
+    DemodAxis, DemodulationConfig, DemodulationFilter, DisplayTransfer, DropoutCompensation,
+    LegalizerMode, MatrixBasis, OutputConfig, PhosphorMask, PipelineConfig, PrecisionConfig,
+    RasterView, ReceiverConfig, SignalDomain, TapeConfig, TapeDamageEvent, TapeDamageKind,
+    VbiConflict, VitcConfig, VitsConfig,
 };
+pub use dsp::filter::FilterPrototype;
 pub use dsp::{rgb_to_yiq, yiq_to_rgb, CompositeSample, Yiq};
-pub use pipeline::{process_frame, Frame, FrameFormat};
+pub use pipeline::{illegal_mask, process_frame, Frame, FrameFormat, Pipeline};
 pub use pipeline::process_frame_with_progress;
+pub use timecode::Timecode;
+pub use vitc::{VitcError, VitcRead, VitcReport};
+pub use vits::{VitsMeasurement, VitsSignal};
@@ -1,7 +1,20 @@
-use crate::config::{DemodulationFilter, PipelineConfig};
+use std::ops::Range;
+
+use crate::captions::{line21_waveform, read_line21, CaptionReport, CaptionTrack, CAPTION_LINE};
+use crate::display::{render_crt, PhosphorState};
+use crate::timecode::Timecode;
+use crate::vitc::{read_vitc, vitc_waveform, VitcRead, VitcReport};
+use crate::vits::{vits_line, VitsInput, VitsMeasurement, VitsSignal};
+use crate::config::{
+    CaptureConfig, ChannelConfig, ClampMode, CompositeConfig, CopyProtectionConfig, DemodAxis, DemodulationConfig,
+    DemodulationFilter, DropoutCompensation, LegalizerMode, PipelineConfig, RasterView,
+    ReceiverConfig, SignalDomain, TapeDamageEvent, TapeDamageKind, VbiConflict,
+};
+use crate::dsp::filter::{BiquadCascade, FilterSpec, FirFilter};
+use crate::dsp::noise::{db_to_amplitude, BandLimitedNoise, GaussianNoise, PinkNoise};
 use crate::dsp::{
-    linear_to_srgb, rgb_to_yiq, soft_clip, srgb_to_linear, yiq_to_rgb, CompositeSample, LowpassFilter,
-    PhasePll, SimpleRng, Yiq,
+    line_start_phase, linear_to_srgb, rgb_to_yiq, soft_clip, srgb_to_linear, yiq_to_rgb,
+    CompositeSample, PhasePll, SimpleRng, Yiq, LINES_PER_FRAME, SUBCARRIER_HZ,
 };
 
 struct BoxFilter {
@@ -86,215 +99,343 @@ impl Frame {
     }
 }
 
-pub fn process_frame(frame: &Frame, config: &PipelineConfig, sample_rate_hz: f32) -> Frame {
-    let subcarrier_hz = 3_579_545.0;
-    let mut out = Frame::new(frame.width, frame.height);
-    let phase_deg = config.composite.subcarrier_phase_deg;
-    let phase_offset = phase_deg.to_radians();
-    let oversample = config.precision.oversample_factor.max(1) as usize;
-    let effective_sample_rate = sample_rate_hz.max(1.0) * oversample as f32;
-    let phase_step = 2.0 * std::f32::consts::PI * subcarrier_hz / effective_sample_rate;
-    let luma_cutoff_hz = config.channel.luma_bandwidth_mhz.max(0.1) * 1_000_000.0;
-    let chroma_cutoff_hz = config.channel.chroma_bandwidth_mhz.max(0.1) * 1_000_000.0;
-    let vhs_chroma_cutoff_hz = config.precision.vhs_chroma_bandwidth_mhz.max(0.1) * 1_000_000.0;
-    let i_cutoff_hz = chroma_cutoff_hz.min(1_300_000.0).min(vhs_chroma_cutoff_hz);
-    let q_cutoff_hz = chroma_cutoff_hz.min(500_000.0).min(vhs_chroma_cutoff_hz);
-    let mut encoder_i_filter = LowpassFilter::new(i_cutoff_hz, effective_sample_rate);
-    let mut encoder_q_filter = LowpassFilter::new(q_cutoff_hz, effective_sample_rate);
-    let mut y_filter = LowpassFilter::new(luma_cutoff_hz, effective_sample_rate);
-    let mut i_filter = LowpassFilter::new(i_cutoff_hz, effective_sample_rate);
-    let mut q_filter = LowpassFilter::new(q_cutoff_hz, effective_sample_rate);
-    let mut luma_highpass = 0.0_f32;
-    let mut chroma_delay = 0.0_f32;
-    let mut pll = PhasePll::new(phase_offset, config.precision.pll_lock_slew);
-    let mut rng = SimpleRng::new(0x1a2b3c4d);
-    let mut i_box = BoxFilter::new(config.demodulation.box_kernel);
-    let mut q_box = BoxFilter::new(config.demodulation.box_kernel);
-    let mut previous_line = vec![0.0_f32; frame.width];
-    let mut previous_line_2 = vec![0.0_f32; frame.width];
-    let mut dropout_remaining = 0usize;
-    let resample_taps = if config.precision.fix_vertical_stripes {
-        config.precision.resample_taps
-    } else {
-        4
-    };
-    let resampler = SincResampler::new(resample_taps as usize);
-    let mut yiq_line = vec![Yiq { y: 0.0, i: 0.0, q: 0.0 }; frame.width];
-    let mut i_line = vec![0.0_f32; frame.width];
-    let mut q_line = vec![0.0_f32; frame.width];
+pub const FRAME_RATE_HZ: f32 = 30_000.0 / 1_001.0;
 
-    for y in 0..frame.height {
-        for x in 0..frame.width {
-            let idx = (y * frame.width + x) * 3;
-            let r = srgb_to_linear(frame.data[idx].clamp(0.0, 1.0));
-            let g = srgb_to_linear(frame.data[idx + 1].clamp(0.0, 1.0));
-            let b = srgb_to_linear(frame.data[idx + 2].clamp(0.0, 1.0));
-            let mut yiq = rgb_to_yiq(r, g, b);
-            yiq.i = encoder_i_filter.process(yiq.i);
-            yiq.q = encoder_q_filter.process(yiq.q);
-            yiq_line[x] = yiq;
-        }
-
-        let samples_per_line = frame.width * oversample;
-        let mut composite_line = vec![0.0_f32; samples_per_line];
-        let mut cos_line = vec![0.0_f32; samples_per_line];
-        let mut sin_line = vec![0.0_f32; samples_per_line];
-
-        for s in 0..samples_per_line {
-            let pixel = s / oversample;
-            let yiq = yiq_line[pixel];
-            let sample_index = (y * samples_per_line + s) as f32;
-            let base_phase = phase_offset + phase_step * sample_index;
-            let jitter_phase = apply_timebase_jitter(
-                y,
-                frame.height,
-                base_phase,
-                &mut rng,
-                &config.artifacts,
-            );
-            let drift_phase = apply_chroma_phase_drift(
-                sample_index,
-                jitter_phase,
-                &config.artifacts,
-            );
-            let pll_phase = pll.update(
-                drift_phase,
-                config.precision.pll_phase_noise,
-                rng.next_signed(),
-            );
-            let mut composite = encode_composite_with_phase(yiq, pll_phase);
-            let degraded = apply_channel(composite, &config.channel);
-            composite = apply_tape(degraded, &config.tape);
-            apply_head_switching(
-                &mut composite,
-                y,
-                frame.height,
-                &mut rng,
-                &config.artifacts,
-            );
-            if config.artifacts.dropout_enabled {
-                apply_dropout(&mut composite, &mut rng, &mut dropout_remaining, &config.artifacts);
-            }
-            composite.voltage = apply_saturation(composite.voltage, &config.artifacts);
-            composite_line[s] = composite.voltage;
-            cos_line[s] = pll_phase.cos();
-            sin_line[s] = pll_phase.sin();
+/// Stateful renderer for frame sequences. Holds the frame counter that drives the
+/// tape timeline and the deck state that carries over between frames.
+pub struct Pipeline {
+    config: PipelineConfig,
+    sample_rate_hz: f32,
+    state: PipelineState,
+    captions: Option<CaptionTrack>,
+    /// Frame index at which the caption track was set; its pair 0 goes out on this frame.
+    caption_start: u64,
+    caption_report: CaptionReport,
+    vitc_report: VitcReport,
+}
+
+impl Pipeline {
+    pub fn new(config: PipelineConfig, sample_rate_hz: f32) -> Self {
+        Self {
+            config,
+            sample_rate_hz,
+            state: PipelineState::new(0x1a2b3c4d),
+            captions: None,
+            caption_start: 0,
+            caption_report: CaptionReport::default(),
+            vitc_report: VitcReport::default(),
         }
+    }
 
-        for x in 0..frame.width {
-            let idx = (y * frame.width + x) * 3;
-            let mut decoded = Yiq { y: 0.0, i: 0.0, q: 0.0 };
-            let sample_pos = (x as f32 + 0.5) * oversample as f32;
-            let composite = resampler.sample(&composite_line, sample_pos);
-            let cos_phase = resampler.sample(&cos_line, sample_pos);
-            let sin_phase = resampler.sample(&sin_line, sample_pos);
-            let sample = CompositeSample {
-                voltage: composite,
-                phase_rad: 0.0,
-            };
-            decoded = decode_composite_stateful(
-                sample,
-                cos_phase,
-                sin_phase,
-                &mut y_filter,
-                &mut i_filter,
-                &mut q_filter,
-                &mut i_box,
-                &mut q_box,
-                &mut previous_line,
-                &mut previous_line_2,
-                x,
-                &mut luma_highpass,
-                &mut chroma_delay,
-                &config.demodulation,
-                &config.artifacts,
-                config.precision.chroma_delay_variation,
-            );
-            i_line[x] = decoded.i;
-            q_line[x] = decoded.q;
+    pub fn config(&self) -> &PipelineConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: PipelineConfig) {
+        self.config = config;
+    }
+
+    pub fn frame_index(&self) -> u64 {
+        self.state.frame_index
+    }
+
+    pub fn time_s(&self) -> f32 {
+        self.state.frame_index as f32 / FRAME_RATE_HZ
+    }
+
+    pub fn seek(&mut self, frame_index: u64) {
+        self.state = PipelineState::new(self.state.seed);
+        self.state.frame_index = frame_index;
+        self.vitc_report = VitcReport::default();
+    }
+
+    /// Sends `captions` on line 21, its first pair on the current frame, and restarts the
+    /// caption report. `None` leaves line 21 blank, as do frames seeked to before the start.
+    pub fn set_captions(&mut self, captions: Option<CaptionTrack>) {
+        self.captions = captions;
+        self.caption_start = self.state.frame_index;
+        self.caption_report = CaptionReport::default();
+    }
+
+    /// VBI lines where one signal replaces another, given the current caption track.
+    pub fn vbi_conflicts(&self) -> Vec<VbiConflict> {
+        self.config.vbi_conflicts(self.captions.is_some())
+    }
+
+    /// Caption bytes sent and read back since the track was set.
+    pub fn caption_report(&self) -> CaptionReport {
+        self.caption_report
+    }
+
+    /// Caption pair the slicer read from line 21 of the last rendered frame.
+    pub fn caption_read(&self) -> Option<[u8; 2]> {
+        self.state.caption_read
+    }
+
+    /// VITC read back from the last rendered frame, one entry per configured line.
+    pub fn vitc(&self) -> &[VitcRead] {
+        &self.state.vitc
+    }
 
-            let (out_r, out_g, out_b) = yiq_to_rgb(decoded);
-            out.data[idx] = linear_to_srgb(out_r).clamp(0.0, 1.0);
-            out.data[idx + 1] = linear_to_srgb(out_g).clamp(0.0, 1.0);
-            out.data[idx + 2] = linear_to_srgb(out_b).clamp(0.0, 1.0);
+    /// VITC lines read back since the pipeline was created or last seeked.
+    pub fn vitc_report(&self) -> VitcReport {
+        self.vitc_report
+    }
+
+    /// Test-signal measurements from the VITS lines of the last rendered frame.
+    pub fn vits(&self) -> &VitsMeasurement {
+        &self.state.vits
+    }
+
+    pub fn process(&mut self, frame: &Frame) -> Frame {
+        self.process_with_progress(frame, |_| {})
+    }
+
+    pub fn process_with_progress<F>(&mut self, frame: &Frame, on_progress: F) -> Frame
+    where
+        F: FnMut(f32),
+    {
+        let frame_index = self.state.frame_index;
+        self.state.caption = self.captions.as_ref().and_then(|track| {
+            let offset = frame_index.checked_sub(self.caption_start)?;
+            Some(track.pair(offset))
+        });
+        self.state.caption_read = None;
+        let output =
+            render_frame(frame, &self.config, self.sample_rate_hz, &mut self.state, on_progress);
+        if let Some(sent) = self.state.caption {
+            self.caption_report.record(sent, self.state.caption_read);
+        }
+        let sent = Timecode::from_frame_index(frame_index, self.config.vitc.drop_frame);
+        for read in &self.state.vitc {
+            self.vitc_report.record(sent, read);
         }
+        output
+    }
+}
+
+struct PipelineState {
+    seed: u32,
+    frame_index: u64,
+    dropout: DropoutState,
+    /// The VBI runs its own DOC delay line, so the first picture line is never patched
+    /// from blanking.
+    vbi_dropout: DropoutState,
+    coupling: AcCoupling,
+    agc: AgcState,
+    burst_lock: BurstLock,
+    chroma_control: ChromaControl,
+    /// Decoder input of the last two frames (most recent first), kept for the 3D comb.
+    frame_history: Vec<Vec<DecodeLine>>,
+    /// Caption pair sent on line 21 this frame, and what the slicer read back.
+    caption: Option<[u8; 2]>,
+    caption_read: Option<[u8; 2]>,
+    /// VITC lines read back this frame.
+    vitc: Vec<VitcRead>,
+    vits: VitsMeasurement,
+    /// CRT afterglow carried into the next frame.
+    phosphor: PhosphorState,
+}
 
-        apply_chroma_blur(&mut i_line, &mut q_line, config.channel.chroma_bandwidth_mhz);
-        if config.debug.diagnostic_mode {
-            apply_diagnostics(&mut out, y, &composite_line, &i_line, &q_line, &resampler, config);
+impl PipelineState {
+    fn new(seed: u32) -> Self {
+        Self {
+            seed,
+            frame_index: 0,
+            dropout: DropoutState::default(),
+            vbi_dropout: DropoutState::default(),
+            coupling: AcCoupling::default(),
+            agc: AgcState::default(),
+            burst_lock: BurstLock::default(),
+            chroma_control: ChromaControl::default(),
+            frame_history: Vec::new(),
+            caption: None,
+            caption_read: None,
+            vitc: Vec::new(),
+            vits: VitsMeasurement::default(),
+            phosphor: PhosphorState::default(),
         }
     }
 
-    out
+    fn frame_seed(&self) -> u32 {
+        self.seed
+            .wrapping_add((self.frame_index as u32).wrapping_mul(0x9e3779b9))
+    }
+}
+
+pub fn process_frame(frame: &Frame, config: &PipelineConfig, sample_rate_hz: f32) -> Frame {
+    let mut state = PipelineState::new(0x1a2b3c4d);
+    render_frame(frame, config, sample_rate_hz, &mut state, |_| {})
 }
 
 pub fn process_frame_with_progress<F>(
     frame: &Frame,
     config: &PipelineConfig,
     sample_rate_hz: f32,
+    on_progress: F,
+) -> Frame
+where
+    F: FnMut(f32),
+{
+    let mut state = PipelineState::new(0x1234abcd);
+    render_frame(frame, config, sample_rate_hz, &mut state, on_progress)
+}
+
+fn render_frame<F>(
+    frame: &Frame,
+    config: &PipelineConfig,
+    sample_rate_hz: f32,
+    state: &mut PipelineState,
     mut on_progress: F,
 ) -> Frame
 where
     F: FnMut(f32),
 {
-    let subcarrier_hz = 3_579_545.0;
+    // The raster views keep NTSC line timing: the picture is resampled to the active samples
+    // of one line period, so a whole line fills the 910 columns at the right scale.
+    let raster_input;
+    let frame = match raster_picture_width(sample_rate_hz) {
+        width if config.debug.raster_view != RasterView::Active && frame.width != width => {
+            raster_input = resample_width(frame, width, config.precision.resample_taps);
+            &raster_input
+        }
+        _ => frame,
+    };
+    let subcarrier_hz = SUBCARRIER_HZ;
     let mut out = Frame::new(frame.width, frame.height);
     let phase_deg = config.composite.subcarrier_phase_deg;
     let phase_offset = phase_deg.to_radians();
     let oversample = config.precision.oversample_factor.max(1) as usize;
     let effective_sample_rate = sample_rate_hz.max(1.0) * oversample as f32;
     let phase_step = 2.0 * std::f32::consts::PI * subcarrier_hz / effective_sample_rate;
-    let luma_cutoff_hz = config.channel.luma_bandwidth_mhz.max(0.1) * 1_000_000.0;
-    let chroma_cutoff_hz = config.channel.chroma_bandwidth_mhz.max(0.1) * 1_000_000.0;
-    let vhs_chroma_cutoff_hz = config.precision.vhs_chroma_bandwidth_mhz.max(0.1) * 1_000_000.0;
-    let i_cutoff_hz = chroma_cutoff_hz.min(1_300_000.0).min(vhs_chroma_cutoff_hz);
-    let q_cutoff_hz = chroma_cutoff_hz.min(500_000.0).min(vhs_chroma_cutoff_hz);
-    let mut encoder_i_filter = LowpassFilter::new(i_cutoff_hz, effective_sample_rate);
-    let mut encoder_q_filter = LowpassFilter::new(q_cutoff_hz, effective_sample_rate);
-    let mut y_filter = LowpassFilter::new(luma_cutoff_hz, effective_sample_rate);
-    let mut i_filter = LowpassFilter::new(i_cutoff_hz, effective_sample_rate);
-    let mut q_filter = LowpassFilter::new(q_cutoff_hz, effective_sample_rate);
-    let mut luma_highpass = 0.0_f32;
-    let mut chroma_delay = 0.0_f32;
-    let mut pll = PhasePll::new(phase_offset, config.precision.pll_lock_slew);
-    let mut rng = SimpleRng::new(0x1234abcd);
-    let mut i_box = BoxFilter::new(config.demodulation.box_kernel);
-    let mut q_box = BoxFilter::new(config.demodulation.box_kernel);
-    let mut previous_line = vec![0.0_f32; frame.width];
-    let mut previous_line_2 = vec![0.0_f32; frame.width];
-    let mut dropout_remaining = 0usize;
+    let half_cycle = ((effective_sample_rate / subcarrier_hz) * 0.5).round().max(1.0) as usize;
+    let (i_cutoff_hz, q_cutoff_hz) = chroma_cutoffs(config);
+    let mut encoder_i_filter = channel_lowpass(&config.channel, i_cutoff_hz, effective_sample_rate);
+    let mut encoder_q_filter = channel_lowpass(&config.channel, q_cutoff_hz, effective_sample_rate);
+    let mut decoder = DecoderState::new(config, frame.width, effective_sample_rate);
+    let samples_per_line = frame.width * oversample;
+    let blanking = HorizontalBlanking::new(effective_sample_rate);
+    let line_len = blanking.len + samples_per_line;
+    let mut line_phase = phase_offset + line_start_phase(state.frame_index, 0);
+    let mut pll = PhasePll::new(
+        line_phase - phase_step * blanking.len as f32,
+        config.precision.pll_lock_slew,
+    );
+    let mut rng = SimpleRng::new(state.frame_seed());
+    let frame_time_s = state.frame_index as f32 / FRAME_RATE_HZ;
     let resample_taps = if config.precision.fix_vertical_stripes {
         config.precision.resample_taps
     } else {
         4
     };
     let resampler = SincResampler::new(resample_taps as usize);
+    let luma_kernel = luma_channel_kernel(&config.channel, effective_sample_rate);
+    let yc_delay = YcDelay::new(&config.channel, effective_sample_rate);
+    let mut noise = NoiseSources::new(config, effective_sample_rate, state.frame_seed());
     let mut yiq_line = vec![Yiq { y: 0.0, i: 0.0, q: 0.0 }; frame.width];
     let mut i_line = vec![0.0_f32; frame.width];
     let mut q_line = vec![0.0_f32; frame.width];
+    let mut lines = Vec::with_capacity(frame.height);
+    let adaptive = matches!(
+        config.demodulation.filter,
+        DemodulationFilter::AdaptiveComb | DemodulationFilter::Comb3D
+    );
 
-    for y in 0..frame.height {
-        for x in 0..frame.width {
-            let idx = (y * frame.width + x) * 3;
-            let r = srgb_to_linear(frame.data[idx].clamp(0.0, 1.0));
-            let g = srgb_to_linear(frame.data[idx + 1].clamp(0.0, 1.0));
-            let b = srgb_to_linear(frame.data[idx + 2].clamp(0.0, 1.0));
-            let mut yiq = rgb_to_yiq(r, g, b);
-            yiq.i = encoder_i_filter.process(yiq.i);
-            yiq.q = encoder_q_filter.process(yiq.q);
-            yiq_line[x] = yiq;
-        }
-
-        let samples_per_line = frame.width * oversample;
-        let mut composite_line = vec![0.0_f32; samples_per_line];
-        let mut cos_line = vec![0.0_f32; samples_per_line];
-        let mut sin_line = vec![0.0_f32; samples_per_line];
-
-        for s in 0..samples_per_line {
-            let pixel = s / oversample;
-            let yiq = yiq_line[pixel];
-            let sample_index = (y * samples_per_line + s) as f32;
-            let base_phase = phase_offset + phase_step * sample_index;
+    let clip_picture = config.composite.legalizer == LegalizerMode::Clip;
+    let (legal_min, legal_max) = legal_range(&config.composite);
+    let illegal = (config.debug.diagnostic_mode && config.debug.show_illegal)
+        .then(|| illegal_mask(frame, config));
+
+    let burst_amplitude = config.composite.burst_amplitude.max(0.0);
+    let timecode = Timecode::from_frame_index(state.frame_index, config.vitc.drop_frame);
+    state.vitc.clear();
+    let mut vits = VitsMeasurement::default();
+    let raster_view = config.debug.raster_view;
+    let mut raster_lines = Vec::new();
+    let mut composite_vits = VitsMeasurement::default();
+    let mut digitizer = Digitizer::new(&config.capture, effective_sample_rate, state.frame_seed());
+
+    // The VBI lines go first; picture line `y` is raster line `VBI_LINES + y`.
+    for raster in 0..VBI_LINES + frame.height {
+        let picture_line = raster.checked_sub(VBI_LINES);
+        let y = picture_line.unwrap_or(0);
+        let mut pseudo_syncs = Vec::new();
+        let held_line: Vec<Yiq> = match picture_line {
+            Some(y) => {
+                for (x, yiq_out) in yiq_line.iter_mut().enumerate() {
+                    let idx = (y * frame.width + x) * 3;
+                    let r = encode_component(frame.data[idx], &config.composite);
+                    let g = encode_component(frame.data[idx + 1], &config.composite);
+                    let b = encode_component(frame.data[idx + 2], &config.composite);
+                    let mut yiq = rgb_to_yiq(r, g, b);
+                    yiq.i = encoder_i_filter.process(yiq.i);
+                    yiq.q = encoder_q_filter.process(yiq.q);
+                    *yiq_out = legalize(with_setup(yiq, &config.composite), &config.composite);
+                }
+                let (i, q) = colorstripe_burst(&config.copy_protection, y);
+                let burst = (i * burst_amplitude, q * burst_amplitude);
+                (0..line_len)
+                    .map(|s| match s.checked_sub(blanking.len) {
+                        Some(active) => yiq_line[active / oversample],
+                        None => blanking.level(s, burst),
+                    })
+                    .collect()
+            }
+            None => {
+                let (mut vbi, gates) = vbi_line(
+                    raster + 1,
+                    &blanking,
+                    line_len,
+                    effective_sample_rate,
+                    &config.copy_protection,
+                    burst_amplitude,
+                    frame_time_s,
+                );
+                pseudo_syncs = gates;
+                let active_len = line_len - blanking.len;
+                let luma_only = |levels: Vec<f32>| -> Vec<Yiq> {
+                    levels.into_iter().map(|y| Yiq { y, i: 0.0, q: 0.0 }).collect()
+                };
+                let payload = match vbi_payload(raster, state.caption, config) {
+                    Some(VbiPayload::Caption(pair)) => luma_only(line21_waveform(pair, active_len)),
+                    Some(VbiPayload::Vitc) => {
+                        luma_only(vitc_waveform(timecode, config.vitc.user_bits, active_len))
+                    }
+                    Some(VbiPayload::Vits(signal)) => {
+                        vits_line(signal, active_len, effective_sample_rate)
+                    }
+                    None => Vec::new(),
+                };
+                for (yiq, data) in vbi[blanking.len..].iter_mut().zip(payload) {
+                    *yiq = data;
+                }
+                vbi
+            }
+        };
+
+        if raster > 0 {
+            let next_line_phase = phase_offset + line_start_phase(state.frame_index, raster);
+            pll.rebase(next_line_phase - (line_phase + phase_step * line_len as f32));
+            line_phase = next_line_phase;
+        }
+        let mut composite_line = vec![0.0_f32; line_len];
+        let mut cos_line = vec![0.0_f32; line_len];
+        let mut sin_line = vec![0.0_f32; line_len];
+        // Only the active part of the line is band limited; blanking and sync are
+        // inserted after the picture chain, so their edges stay clean.
+        let mut luma_line: Vec<f32> = held_line.iter().map(|yiq| yiq.y).collect();
+        let active = luma_kernel.apply(&luma_line[blanking.len..]);
+        luma_line[blanking.len..].copy_from_slice(&active);
+
+        for s in 0..line_len {
+            let mut yiq = held_line[s];
+            yiq.y = luma_line[s];
+            if config.channel.chroma_noise > 0.0 {
+                yiq.i += config.channel.chroma_noise * noise.chroma_i.next_sample();
+                yiq.q += config.channel.chroma_noise * noise.chroma_q.next_sample();
+            }
+            let sample_index = (raster * line_len + s) as f32;
+            let base_phase = line_phase + phase_step * (s as f32 - blanking.len as f32);
             let jitter_phase = apply_timebase_jitter(
                 y,
                 frame.height,
@@ -313,8 +454,11 @@ where
                 rng.next_signed(),
             );
             let mut composite = encode_composite_with_phase(yiq, pll_phase);
-            let degraded = apply_channel(composite, &config.channel);
-            composite = apply_tape(degraded, &config.tape);
+            if clip_picture && picture_line.is_some() && s >= blanking.len {
+                composite.voltage = composite.voltage.clamp(legal_min, legal_max);
+            }
+            let degraded = apply_channel(composite, &config.channel, &mut noise);
+            composite = apply_tape(degraded, &config.tape, &mut noise);
             apply_head_switching(
                 &mut composite,
                 y,
@@ -322,60 +466,928 @@ where
                 &mut rng,
                 &config.artifacts,
             );
-            if config.artifacts.dropout_enabled {
-                apply_dropout(&mut composite, &mut rng, &mut dropout_remaining, &config.artifacts);
-            }
-            composite.voltage = apply_saturation(composite.voltage, &config.artifacts);
             composite_line[s] = composite.voltage;
             cos_line[s] = pll_phase.cos();
             sin_line[s] = pll_phase.sin();
         }
 
-        for x in 0..frame.width {
-            let idx = (y * frame.width + x) * 3;
-            let mut decoded = Yiq { y: 0.0, i: 0.0, q: 0.0 };
-            let sample_pos = (x as f32 + 0.5) * oversample as f32;
-            let composite = resampler.sample(&composite_line, sample_pos);
-            let cos_phase = resampler.sample(&cos_line, sample_pos);
-            let sin_phase = resampler.sample(&sin_line, sample_pos);
+        let line_norm = picture_line.map_or(0.0, |y| y as f32 / frame.height as f32);
+        let line_time_s = frame_time_s + line_norm / FRAME_RATE_HZ;
+        let damage = line_damage(&config.tape.damage_events, line_time_s, line_norm);
+        if damage.timing_shift != 0.0 {
+            shift_line(&mut composite_line, damage.timing_shift * oversample as f32);
+        }
+        if config.artifacts.dropout_enabled || damage.is_active() {
+            let dropout = match picture_line {
+                Some(_) => &mut state.dropout,
+                None => &mut state.vbi_dropout,
+            };
+            apply_dropouts(&mut composite_line, half_cycle, &damage, dropout, &mut rng, config);
+        }
+        if let Some(yc_delay) = &yc_delay {
+            yc_delay.apply(&mut composite_line);
+        }
+        apply_differential_gain_phase(&mut composite_line, half_cycle, &config.channel);
+        state.coupling.process(&mut composite_line, &config.channel, effective_sample_rate);
+        for voltage in composite_line.iter_mut() {
+            *voltage = apply_saturation(*voltage, &config.artifacts);
+        }
+        let keyed = raster >= VERTICAL_INTERVAL_LINES;
+        state.agc.process(&mut composite_line, &blanking, &pseudo_syncs, keyed, &config.receiver);
+        let burst_level =
+            state.burst_lock.track(&composite_line, &mut cos_line, &mut sin_line, &blanking);
+        // The killer and ACC detector are gated off through the vertical interval.
+        if raster >= VERTICAL_INTERVAL_LINES {
+            state.chroma_control.detect(burst_level, &config.receiver);
+        }
+        if raster_view != RasterView::Active {
+            // One raster line spans the view, starting at the sync edge; the front porch
+            // wraps round to the right.
+            let scale = line_len as f32 / RASTER_WIDTH as f32;
+            let sample = |line: &[f32]| -> Vec<f32> {
+                (0..RASTER_WIDTH)
+                    .map(|c| {
+                        let position = blanking.sync_start as f32 + (c as f32 + 0.5) * scale;
+                        resampler.sample(line, position % line_len as f32)
+                    })
+                    .collect()
+            };
+            raster_lines.push(DecodeLine {
+                composite: sample(&composite_line),
+                luma: Vec::new(),
+                cos_phase: sample(&cos_line),
+                sin_phase: sample(&sin_line),
+                chroma_gain: state.chroma_control.gain(&config.receiver),
+            });
+        }
+        if picture_line.is_none() {
+            let active = &composite_line[blanking.len..];
+            match vbi_payload(raster, state.caption, config) {
+                Some(VbiPayload::Caption(_)) => state.caption_read = read_line21(active),
+                Some(VbiPayload::Vitc) => state.vitc.push(VitcRead {
+                    line: raster as u32 + 1,
+                    result: read_vitc(active),
+                }),
+                Some(VbiPayload::Vits(signal)) => {
+                    let input = VitsInput {
+                        line: active,
+                        cos_line: &cos_line[blanking.len..],
+                        sin_line: &sin_line[blanking.len..],
+                        sample_rate_hz: effective_sample_rate,
+                    };
+                    match signal {
+                        VitsSignal::Composite => input.measure(signal, &mut composite_vits),
+                        _ => input.measure(signal, &mut vits),
+                    }
+                }
+                None => {}
+            }
+            continue;
+        }
+
+        let position = |x: usize| blanking.len as f32 + (x as f32 + 0.5) * oversample as f32;
+        let resample = |line: &[f32]| -> Vec<f32> {
+            (0..frame.width).map(|x| resampler.sample(line, position(x))).collect()
+        };
+        let composite_px = match &mut digitizer {
+            Some(adc) => (0..frame.width)
+                .map(|x| {
+                    let instant = adc.sample_position(position(x));
+                    adc.quantize(resampler.sample(&composite_line, instant))
+                })
+                .collect(),
+            None => resample(&composite_line),
+        };
+        let luma = if adaptive {
+            decoder.chroma_notch.apply(&composite_px)
+        } else {
+            Vec::new()
+        };
+        lines.push(DecodeLine {
+            composite: composite_px,
+            luma,
+            cos_phase: resample(&cos_line),
+            sin_phase: resample(&sin_line),
+            chroma_gain: state.chroma_control.gain(&config.receiver),
+        });
+        on_progress(0.5 * (y + 1) as f32 / frame.height as f32);
+    }
+    state.vits = vits.or(composite_vits);
+
+    if raster_view == RasterView::Active {
+        for y in 0..frame.height {
+            let line = &lines[y];
+            if adaptive {
+                let above = y.checked_sub(1).map(|prev| &lines[prev]);
+                decoder.separate_adaptive(above, line, lines.get(y + 1), &config.demodulation);
+            }
+            if config.demodulation.filter == DemodulationFilter::Comb3D {
+                let history =
+                    |age: usize| state.frame_history.get(age).and_then(|frame| frame.get(y));
+                decoder.separate_temporal(history(0), history(1), line, &config.demodulation);
+            }
+            let decoded_line = decode_row(&mut decoder, line, config);
+            for (x, &decoded) in decoded_line.iter().enumerate() {
+                let idx = (y * frame.width + x) * 3;
+                i_line[x] = decoded.i;
+                q_line[x] = decoded.q;
+
+                let (out_r, out_g, out_b) = yiq_to_rgb(decoded);
+                out.data[idx] = display_component(out_r, config);
+                out.data[idx + 1] = display_component(out_g, config);
+                out.data[idx + 2] = display_component(out_b, config);
+            }
+
+            apply_chroma_blur(&mut i_line, &mut q_line, config.channel.chroma_bandwidth_mhz);
+            if config.debug.diagnostic_mode {
+                apply_diagnostics(&mut out, y, &line.composite, &i_line, &q_line, config);
+                if config.debug.show_comb_blend {
+                    apply_comb_blend_diagnostics(&mut out, y, &decoder.comb_blend);
+                }
+                if let Some(mask) = &illegal {
+                    let row = y * frame.width;
+                    for x in (0..frame.width).filter(|&x| mask[row + x]) {
+                        let pixel = (row + x) * 3..(row + x + 1) * 3;
+                        out.data[pixel].copy_from_slice(&[1.0, 0.0, 1.0]);
+                    }
+                }
+            }
+            on_progress(0.5 + 0.5 * (y + 1) as f32 / frame.height as f32);
+        }
+    } else {
+        // The raster replaces the picture, so the active lines are not decoded on their own.
+        let raster_rate =
+            effective_sample_rate * (RASTER_WIDTH * oversample) as f32 / line_len as f32;
+        out = render_raster(&mut raster_lines, raster_view, frame.height, config, raster_rate);
+        on_progress(1.0);
+    }
+    if config.crt.enabled {
+        out = render_crt(&out, &config.crt, &mut state.phosphor);
+    }
+
+    if config.demodulation.filter == DemodulationFilter::Comb3D {
+        state.frame_history.insert(0, lines);
+        state.frame_history.truncate(2);
+    } else {
+        state.frame_history.clear();
+    }
+    state.frame_index += 1;
+    out
+}
+
+/// Decodes one line of the pixel grid to Y'IQ.
+fn decode_row(decoder: &mut DecoderState, line: &DecodeLine, config: &PipelineConfig) -> Vec<Yiq> {
+    decoder.chroma_gain = line.chroma_gain;
+    let mut decoded: Vec<Yiq> = (0..line.composite.len())
+        .map(|x| {
             let sample = CompositeSample {
-                voltage: composite,
+                voltage: line.composite[x],
                 phase_rad: 0.0,
             };
-            decoded = decode_composite_stateful(
-                sample,
-                cos_phase,
-                sin_phase,
-                &mut y_filter,
-                &mut i_filter,
-                &mut q_filter,
-                &mut i_box,
-                &mut q_box,
-                &mut previous_line,
-                &mut previous_line_2,
-                x,
-                &mut luma_highpass,
-                &mut chroma_delay,
-                &config.demodulation,
-                &config.artifacts,
-                config.precision.chroma_delay_variation,
+            let (cos_phase, sin_phase) = (line.cos_phase[x], line.sin_phase[x]);
+            decode_composite_stateful(sample, cos_phase, sin_phase, x, decoder, config)
+        })
+        .collect();
+    if config.capture.enabled {
+        subsample_chroma(&mut decoded, config.capture.chroma_subsampling.factor());
+    }
+    decoded
+}
+
+/// Samples per line of the full-raster view (4fsc).
+const RASTER_WIDTH: usize = 910;
+
+/// Picture width that makes a rendered line one NTSC line period long at `sample_rate_hz`.
+fn raster_picture_width(sample_rate_hz: f32) -> usize {
+    let line_samples = (LINE_PERIOD_S * sample_rate_hz).round() as usize;
+    line_samples.saturating_sub(HorizontalBlanking::new(sample_rate_hz).len).max(1)
+}
+
+fn resample_width(frame: &Frame, width: usize, taps: u8) -> Frame {
+    let resampler = SincResampler::new(taps as usize);
+    let scale = frame.width as f32 / width as f32;
+    let mut out = Frame::new(width, frame.height);
+    let mut channel = vec![0.0_f32; frame.width];
+    for y in 0..frame.height {
+        for c in 0..3 {
+            for (x, value) in channel.iter_mut().enumerate() {
+                *value = frame.data[(y * frame.width + x) * 3 + c];
+            }
+            for x in 0..width {
+                let position = (x as f32 + 0.5) * scale - 0.5;
+                out.data[(y * width + x) * 3 + c] = resampler.sample(&channel, position);
+            }
+        }
+    }
+    out
+}
+const RASTER_LINES: usize = LINES_PER_FRAME as usize;
+/// Lines outside both fields' VBI.
+const RASTER_ACTIVE_LINES: usize = RASTER_LINES - 2 * VBI_LINES;
+/// 10-bit 4fsc composite codes (SMPTE 244M): sync tip 16, blanking 240, white 800.
+const RASTER_BLANKING_CODE: f32 = 240.0;
+const RASTER_WHITE_CODE: f32 = 800.0;
+
+/// Raster line shown on `row` of the full-raster view. The fields interleave and both
+/// repeat the rendered VBI; the picture is sampled onto the 483 active lines.
+fn raster_source(row: usize, picture_lines: usize) -> usize {
+    let (field, field_line) = (row % 2, row / 2);
+    if field_line < VBI_LINES {
+        return field_line;
+    }
+    let active = 2 * (field_line - VBI_LINES) + field;
+    VBI_LINES + (active * picture_lines / RASTER_ACTIVE_LINES).min(picture_lines.saturating_sub(1))
+}
+
+/// Full-raster view from every rendered line, as greyscale composite or decoded with
+/// nothing cropped. `sample_rate_hz` is the encoder rate that puts a line on 910 pixels.
+fn render_raster(
+    lines: &mut [DecodeLine],
+    view: RasterView,
+    picture_lines: usize,
+    config: &PipelineConfig,
+    sample_rate_hz: f32,
+) -> Frame {
+    let rows: Vec<Vec<[f32; 3]>> = match view {
+        RasterView::Underscan => {
+            let mut decoder = DecoderState::new(config, RASTER_WIDTH, sample_rate_hz);
+            let adaptive = matches!(
+                config.demodulation.filter,
+                DemodulationFilter::AdaptiveComb | DemodulationFilter::Comb3D
             );
-            i_line[x] = decoded.i;
-            q_line[x] = decoded.q;
+            if adaptive {
+                for line in lines.iter_mut() {
+                    line.luma = decoder.chroma_notch.apply(&line.composite);
+                }
+            }
+            // There is no raster history, so the 3D comb falls back to its 2D separation.
+            (0..lines.len())
+                .map(|k| {
+                    if adaptive {
+                        let above = k.checked_sub(1).map(|prev| &lines[prev]);
+                        let below = lines.get(k + 1);
+                        decoder.separate_adaptive(above, &lines[k], below, &config.demodulation);
+                    }
+                    decode_row(&mut decoder, &lines[k], config)
+                        .into_iter()
+                        .map(|yiq| {
+                            let (r, g, b) = yiq_to_rgb(yiq);
+                            [r, g, b].map(|value| display_component(value, config))
+                        })
+                        .collect()
+                })
+                .collect()
+        }
+        _ => lines
+            .iter()
+            .map(|line| {
+                line.composite
+                    .iter()
+                    .map(|&voltage| {
+                        let code = RASTER_BLANKING_CODE
+                            + voltage * (RASTER_WHITE_CODE - RASTER_BLANKING_CODE);
+                        [(code / 1023.0).clamp(0.0, 1.0); 3]
+                    })
+                    .collect()
+            })
+            .collect(),
+    };
+    let mut out = Frame::new(RASTER_WIDTH, RASTER_LINES);
+    for (row, pixels) in out.data.chunks_exact_mut(RASTER_WIDTH * 3).enumerate() {
+        let source = &rows[raster_source(row, picture_lines)];
+        for (pixel, rgb) in pixels.chunks_exact_mut(3).zip(source) {
+            pixel.copy_from_slice(rgb);
+        }
+    }
+    out
+}
 
-            let (out_r, out_g, out_b) = yiq_to_rgb(decoded);
-            out.data[idx] = linear_to_srgb(out_r).clamp(0.0, 1.0);
-            out.data[idx + 1] = linear_to_srgb(out_g).clamp(0.0, 1.0);
-            out.data[idx + 2] = linear_to_srgb(out_b).clamp(0.0, 1.0);
+/// Sync tip level, -40 IRE below blanking.
+const SYNC_LEVEL: f32 = -0.4;
+/// Colour burst: 20 IRE peak on -(B-Y), i.e. 180° from B-Y, 57° from I.
+pub(crate) const BURST_IQ: (f32, f32) = (0.109, -0.168);
+const BURST_CYCLES: f32 = 9.0;
+const FRONT_PORCH_S: f32 = 1.5e-6;
+const SYNC_WIDTH_S: f32 = 4.7e-6;
+const BREEZEWAY_S: f32 = 0.6e-6;
+const BACK_PORCH_S: f32 = 1.6e-6;
+
+/// Horizontal blanking ahead of active video: front porch, sync tip, breezeway,
+/// burst and back porch, as sample offsets at the encoder rate.
+struct HorizontalBlanking {
+    sync_start: usize,
+    sync_end: usize,
+    burst_start: usize,
+    back_porch_start: usize,
+    len: usize,
+}
+
+impl HorizontalBlanking {
+    fn new(sample_rate_hz: f32) -> Self {
+        let samples = |seconds: f32| (seconds * sample_rate_hz).round().max(1.0) as usize;
+        let sync_start = samples(FRONT_PORCH_S);
+        let sync_end = sync_start + samples(SYNC_WIDTH_S);
+        let burst_start = sync_end + samples(BREEZEWAY_S);
+        let back_porch_start = burst_start + samples(BURST_CYCLES / SUBCARRIER_HZ);
+        Self {
+            sync_start,
+            sync_end,
+            burst_start,
+            back_porch_start,
+            len: back_porch_start + samples(BACK_PORCH_S),
         }
-        apply_chroma_blur(&mut i_line, &mut q_line, config.channel.chroma_bandwidth_mhz);
-        if config.debug.diagnostic_mode {
-            apply_diagnostics(&mut out, y, &composite_line, &i_line, &q_line, &resampler, config);
+    }
+
+    fn level(&self, s: usize, burst: (f32, f32)) -> Yiq {
+        let (y, (i, q)) = if (self.sync_start..self.sync_end).contains(&s) {
+            (SYNC_LEVEL, (0.0, 0.0))
+        } else if self.burst().contains(&s) {
+            (0.0, burst)
+        } else {
+            (0.0, (0.0, 0.0))
+        };
+        Yiq { y, i, q }
+    }
+
+    fn burst(&self) -> Range<usize> {
+        self.burst_start..self.back_porch_start
+    }
+
+    fn key_gate(&self) -> KeyGate {
+        KeyGate {
+            tip: middle_half(self.sync_start..self.sync_end),
+            porch: self.back_porch_start..self.len,
         }
-        on_progress((y + 1) as f32 / frame.height as f32);
     }
+}
 
-    out
+/// Where the receiver samples sync tip and porch after a sync edge.
+struct KeyGate {
+    tip: Range<usize>,
+    porch: Range<usize>,
+}
+
+/// Middle half of a pulse, clear of the filtered edges.
+fn middle_half(pulse: Range<usize>) -> Range<usize> {
+    let quarter = pulse.len() / 4;
+    pulse.start + quarter..pulse.end - quarter
+}
+
+/// Blanking lines ahead of the picture (lines 1-21 of a field); 1-9 are the vertical
+/// interval and carry no burst.
+const VBI_LINES: usize = 21;
+const VERTICAL_INTERVAL_LINES: usize = 9;
+const PSEUDO_SYNC_S: f32 = 2.3e-6;
+const EQUALIZING_PULSE_S: f32 = 2.3e-6;
+const SERRATION_S: f32 = 4.7e-6;
+const AGC_PULSE_S: f32 = 2.9e-6;
+
+/// Data or test signal in the active part of a VBI line.
+#[derive(Clone, Copy)]
+enum VbiPayload {
+    Caption([u8; 2]),
+    Vitc,
+    Vits(VitsSignal),
+}
+
+/// What raster line `raster` carries: captions take line 21, then VITC, then VITS.
+fn vbi_payload(
+    raster: usize,
+    caption: Option<[u8; 2]>,
+    config: &PipelineConfig,
+) -> Option<VbiPayload> {
+    let line_number = raster as u32 + 1;
+    match caption {
+        Some(pair) if raster + 1 == CAPTION_LINE => Some(VbiPayload::Caption(pair)),
+        _ if config.vitc.enabled && config.vitc.lines.contains(&line_number) => {
+            Some(VbiPayload::Vitc)
+        }
+        _ if config.vits.enabled => config.vits.signal(line_number).map(VbiPayload::Vits),
+        _ => None,
+    }
+}
+
+/// Builds VBI line `line_number` (1-based) at the encoder rate, with the copy-protection
+/// pulse train if enabled. Returns the line and a key gate for every pseudo-sync in it.
+fn vbi_line(
+    line_number: usize,
+    blanking: &HorizontalBlanking,
+    line_len: usize,
+    sample_rate_hz: f32,
+    protection: &CopyProtectionConfig,
+    burst_amplitude: f32,
+    time_s: f32,
+) -> (Vec<Yiq>, Vec<KeyGate>) {
+    let burst = if line_number > VERTICAL_INTERVAL_LINES {
+        (BURST_IQ.0 * burst_amplitude, BURST_IQ.1 * burst_amplitude)
+    } else {
+        (0.0, 0.0)
+    };
+    let mut line: Vec<Yiq> = (0..line_len).map(|s| blanking.level(s, burst)).collect();
+    if line_number <= VERTICAL_INTERVAL_LINES {
+        vertical_sync(&mut line, line_number, blanking, sample_rate_hz);
+    }
+    let mut gates = Vec::new();
+    let first = protection.agc_pulse_first_line as usize;
+    let last = protection.agc_pulse_last_line as usize;
+    if !protection.agc_pulses_enabled || !(first..=last).contains(&line_number) {
+        return (line, gates);
+    }
+    let cycle = if protection.agc_cycle_s > 0.0 {
+        0.5 + 0.5 * (std::f32::consts::TAU * time_s / protection.agc_cycle_s).cos()
+    } else {
+        1.0
+    };
+    let level = protection.agc_pulse_peak_ire * 0.01 * cycle;
+    let pulses = protection.agc_pulses_per_line.max(1) as usize;
+    let slot = (line_len - blanking.len) / pulses;
+    let sync_len = ((PSEUDO_SYNC_S * sample_rate_hz) as usize).min(slot / 2);
+    let pulse_len = ((AGC_PULSE_S * sample_rate_hz) as usize).min(slot - sync_len);
+    for n in 0..pulses {
+        let start = blanking.len + n * slot;
+        let pulse = start + sync_len..start + sync_len + pulse_len;
+        line[start..pulse.start].iter_mut().for_each(|yiq| yiq.y = SYNC_LEVEL);
+        line[pulse.clone()].iter_mut().for_each(|yiq| yiq.y = level);
+        gates.push(KeyGate {
+            tip: middle_half(start..pulse.start),
+            porch: middle_half(pulse),
+        });
+    }
+    (line, gates)
+}
+
+/// Field 1 vertical interval: equalizing pulses on lines 1-3 and 7-9 and serrated broad
+/// pulses on 4-6, all at twice the line rate.
+fn vertical_sync(
+    line: &mut [Yiq],
+    line_number: usize,
+    blanking: &HorizontalBlanking,
+    sample_rate_hz: f32,
+) {
+    let half = line.len() / 2;
+    let width = if (4..=6).contains(&line_number) {
+        half - (SERRATION_S * sample_rate_hz) as usize
+    } else {
+        (EQUALIZING_PULSE_S * sample_rate_hz) as usize
+    };
+    line.iter_mut().for_each(|yiq| yiq.y = 0.0);
+    for start in [blanking.sync_start, blanking.sync_start + half] {
+        let end = (start + width).min(line.len());
+        line[start..end].iter_mut().for_each(|yiq| yiq.y = SYNC_LEVEL);
+    }
+}
+
+/// Burst for picture line `y`: rotated by the colorstripe phase on striped lines.
+fn colorstripe_burst(protection: &CopyProtectionConfig, y: usize) -> (f32, f32) {
+    let period = protection.colorstripe_period_lines.max(1) as usize;
+    if !protection.colorstripe_enabled || y % period >= protection.colorstripe_lines_on as usize {
+        return BURST_IQ;
+    }
+    let (sin, cos) = protection.colorstripe_phase_deg.to_radians().sin_cos();
+    let (i, q) = BURST_IQ;
+    (i * cos + q * sin, q * cos - i * sin)
+}
+
+const BURST_LOCK_GAIN: f32 = 0.3;
+
+/// Decoder subcarrier regenerated from burst: a first-order loop on the burst phase of
+/// each line, applied from that line's picture on.
+#[derive(Default)]
+struct BurstLock {
+    offset: f32,
+}
+
+impl BurstLock {
+    /// Returns the line's burst amplitude relative to nominal.
+    fn track(&mut self, line: &[f32], cos_line: &mut [f32], sin_line: &mut [f32], blanking: &HorizontalBlanking) -> f32 {
+        let burst = blanking.burst();
+        let scale = 2.0 / burst.len().max(1) as f32;
+        let (i, q) = burst.fold((0.0, 0.0), |(i, q), s| {
+            (i + line[s] * cos_line[s], q + line[s] * sin_line[s])
+        });
+        let (i, q) = (i * scale, q * scale);
+        let nominal = BURST_IQ.1.hypot(BURST_IQ.0);
+        let level = i.hypot(q) / nominal;
+        if level > 0.25 {
+            let error = q.atan2(i) - BURST_IQ.1.atan2(BURST_IQ.0);
+            self.offset += BURST_LOCK_GAIN * wrap_phase(-error - self.offset);
+        }
+        if self.offset != 0.0 {
+            let (sin, cos) = self.offset.sin_cos();
+            for (c, s) in cos_line.iter_mut().zip(sin_line.iter_mut()) {
+                (*c, *s) = (*c * cos - *s * sin, *s * cos + *c * sin);
+            }
+        }
+        level
+    }
+}
+
+/// Receiver ACC and colour killer fed by a smoothed burst amplitude detector. The killer
+/// has hysteresis, so a burst hovering near the threshold flickers between colour and
+/// black-and-white rather than chattering on every line.
+struct ChromaControl {
+    burst_level: f32,
+    killed: bool,
+}
+
+impl Default for ChromaControl {
+    fn default() -> Self {
+        Self {
+            burst_level: 1.0,
+            killed: false,
+        }
+    }
+}
+
+impl ChromaControl {
+    fn detect(&mut self, burst_level: f32, receiver: &ReceiverConfig) {
+        if !receiver.acc_enabled && !receiver.color_killer_enabled {
+            return;
+        }
+        let time_constant_s = receiver.burst_detector_ms.max(0.01) * 0.001;
+        let alpha = 1.0 - (-LINE_PERIOD_S / time_constant_s).exp();
+        self.burst_level += alpha * (burst_level - self.burst_level);
+        let percent = 100.0 * self.burst_level;
+        self.killed = receiver.color_killer_enabled
+            && if self.killed {
+                percent < receiver.color_killer_on_pct.max(receiver.color_killer_off_pct)
+            } else {
+                percent < receiver.color_killer_off_pct
+            };
+    }
+
+    fn gain(&self, receiver: &ReceiverConfig) -> f32 {
+        if self.killed {
+            0.0
+        } else if receiver.acc_enabled {
+            (1.0 / self.burst_level.max(1e-3)).min(receiver.acc_max_gain.max(1.0))
+        } else {
+            1.0
+        }
+    }
+}
+
+fn wrap_phase(phase: f32) -> f32 {
+    (phase + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU) - std::f32::consts::PI
+}
+
+const LINE_PERIOD_S: f32 = 1.0 / (FRAME_RATE_HZ * LINES_PER_FRAME as f32);
+const AGC_GAIN_RANGE: (f32, f32) = (0.25, 4.0);
+/// The detector charges through a diode and leaks through the loop filter, so gain
+/// reductions act this much faster than the time constant.
+const AGC_ATTACK_SPEEDUP: f32 = 10.0;
+
+/// Receiver input stage: a keyed clamp restores DC from the line's own blanking, and a
+/// first-order AGC loop servoes the sync amplitude it measures at its output.
+struct AgcState {
+    gain: f32,
+    /// Sync tip and porch from the last keyed line, held through the vertical interval.
+    held: Option<(f32, f32)>,
+}
+
+impl Default for AgcState {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            held: None,
+        }
+    }
+}
+
+impl AgcState {
+    fn process(
+        &mut self,
+        line: &mut [f32],
+        blanking: &HorizontalBlanking,
+        pseudo_syncs: &[KeyGate],
+        keyed: bool,
+        receiver: &ReceiverConfig,
+    ) {
+        if receiver.clamp == ClampMode::Off && !receiver.agc_enabled {
+            return;
+        }
+        let mean = |range: &Range<usize>| {
+            let len = range.len().max(1) as f32;
+            line[range.clone()].iter().sum::<f32>() / len
+        };
+        let gate = blanking.key_gate();
+        // Keying is gated off through the vertical interval, where equalizing and broad
+        // pulses would read as a wrong tip or porch.
+        let (tip, porch) = if keyed {
+            let measured = (mean(&gate.tip), mean(&gate.porch));
+            self.held = Some(measured);
+            measured
+        } else {
+            self.held.unwrap_or((SYNC_LEVEL, 0.0))
+        };
+        if !receiver.agc_enabled {
+            self.gain = 1.0;
+        } else if keyed {
+            // Peak detector: pseudo-syncs key the gate too, so an AGC pulse after one
+            // reads as an oversized sync and pulls the gain down.
+            let sync = pseudo_syncs
+                .iter()
+                .map(|gate| mean(&gate.porch) - mean(&gate.tip))
+                .fold(porch - tip, f32::max);
+            let reference = receiver.agc_reference_ire.max(1.0) * 0.01;
+            let measured = (self.gain * sync).max(1e-3);
+            let mut time_constant_s = receiver.agc_time_constant_ms.max(0.01) * 0.001;
+            if measured > reference {
+                time_constant_s /= AGC_ATTACK_SPEEDUP;
+            }
+            let alpha = 1.0 - (-LINE_PERIOD_S / time_constant_s).exp();
+            self.gain = (self.gain * (reference / measured).powf(alpha))
+                .clamp(AGC_GAIN_RANGE.0, AGC_GAIN_RANGE.1);
+        }
+        let (input, output) = match receiver.clamp {
+            ClampMode::Off => (0.0, 0.0),
+            ClampMode::SyncTip => (tip, SYNC_LEVEL),
+            ClampMode::BackPorch => (porch, 0.0),
+        };
+        for voltage in line.iter_mut() {
+            *voltage = (*voltage - input) * self.gain + output;
+        }
+    }
+}
+
+fn chroma_cutoffs(config: &PipelineConfig) -> (f32, f32) {
+    let chroma_cutoff_hz = config.channel.chroma_bandwidth_mhz.max(0.1) * 1_000_000.0;
+    let vhs_chroma_cutoff_hz = config.precision.vhs_chroma_bandwidth_mhz.max(0.1) * 1_000_000.0;
+    let i_cutoff_hz = chroma_cutoff_hz.min(1_300_000.0).min(vhs_chroma_cutoff_hz);
+    let q_cutoff_hz = chroma_cutoff_hz.min(500_000.0).min(vhs_chroma_cutoff_hz);
+    (i_cutoff_hz, q_cutoff_hz)
+}
+
+/// Decoder bandwidth of one demodulator: its own limit, capped by the channel and tape.
+fn axis_cutoff(config: &PipelineConfig, axis: &DemodAxis) -> f32 {
+    let chroma_cutoff_hz = config.channel.chroma_bandwidth_mhz.max(0.1) * 1_000_000.0;
+    let vhs_chroma_cutoff_hz = config.precision.vhs_chroma_bandwidth_mhz.max(0.1) * 1_000_000.0;
+    (axis.bandwidth_mhz.max(0.05) * 1_000_000.0)
+        .min(chroma_cutoff_hz)
+        .min(vhs_chroma_cutoff_hz)
+}
+
+fn channel_lowpass(channel: &ChannelConfig, cutoff_hz: f32, sample_rate_hz: f32) -> BiquadCascade {
+    FilterSpec::new(channel.filter_prototype, channel.filter_order, cutoff_hz).design(sample_rate_hz)
+}
+
+/// One line of composite resampled to the pixel grid, ready for Y/C separation.
+struct DecodeLine {
+    composite: Vec<f32>,
+    /// Notch-filtered composite (horizontal luma estimate); only kept for the adaptive comb.
+    luma: Vec<f32>,
+    cos_phase: Vec<f32>,
+    sin_phase: Vec<f32>,
+    /// ACC / colour killer gain in force on this line.
+    chroma_gain: f32,
+}
+
+/// Per-pixel weights of the adaptive combs: comb with the line above, the line below, the
+/// previous frame, or fall back to notch/bandpass separation. They sum to one.
+#[derive(Debug, Clone, Copy, Default)]
+struct CombBlend {
+    up: f32,
+    down: f32,
+    fallback: f32,
+    temporal: f32,
+}
+
+struct DecoderState {
+    y_filter: BiquadCascade,
+    first_filter: BiquadCascade,
+    second_filter: BiquadCascade,
+    first_box: BoxFilter,
+    second_box: BoxFilter,
+    references: [(f32, f32); 2],
+    matrix: [[f32; 2]; 2],
+    sharpness_lowpass: BiquadCascade,
+    previous_line: Vec<f32>,
+    previous_line_2: Vec<f32>,
+    luma_highpass: f32,
+    chroma_delay: f32,
+    chroma_notch: FirFilter,
+    adaptive_chroma: Vec<f32>,
+    comb_blend: Vec<CombBlend>,
+    chroma_gain: f32,
+}
+
+impl DecoderState {
+    fn new(config: &PipelineConfig, width: usize, sample_rate_hz: f32) -> Self {
+        let luma_cutoff_hz = config.channel.luma_bandwidth_mhz.max(0.1) * 1_000_000.0;
+        let (i_cutoff_hz, _) = chroma_cutoffs(config);
+        let axes = &config.demodulation.axes;
+        let pixel_rate_hz = sample_rate_hz / config.precision.oversample_factor.max(1) as f32;
+        Self {
+            y_filter: channel_lowpass(&config.channel, luma_cutoff_hz, pixel_rate_hz),
+            first_filter: channel_lowpass(
+                &config.channel,
+                axis_cutoff(config, &axes.first),
+                pixel_rate_hz,
+            ),
+            second_filter: channel_lowpass(
+                &config.channel,
+                axis_cutoff(config, &axes.second),
+                pixel_rate_hz,
+            ),
+            first_box: BoxFilter::new(config.demodulation.box_kernel),
+            second_box: BoxFilter::new(config.demodulation.box_kernel),
+            references: axes.references(),
+            matrix: axes.matrix(),
+            sharpness_lowpass: channel_lowpass(&config.channel, RECEIVER_PEAKING_HZ, pixel_rate_hz),
+            previous_line: vec![0.0; width],
+            previous_line_2: vec![0.0; width],
+            luma_highpass: 0.0,
+            chroma_delay: 0.0,
+            chroma_notch: FirFilter::windowed_sinc_notch(SUBCARRIER_HZ, 2.0 * i_cutoff_hz, pixel_rate_hz),
+            adaptive_chroma: vec![0.0; width],
+            comb_blend: vec![CombBlend::default(); width],
+            chroma_gain: 1.0,
+        }
+    }
+
+    /// Multiplies by each demodulator's reference carrier; outputs are still unfiltered.
+    fn demodulate(&self, chroma: f32, cos_phase: f32, sin_phase: f32) -> (f32, f32) {
+        let [(c1, s1), (c2, s2)] = self.references;
+        (chroma * (cos_phase * c1 + sin_phase * s1), chroma * (cos_phase * c2 + sin_phase * s2))
+    }
+
+    /// Lowpasses both demodulator outputs and matrixes them to I/Q.
+    fn demodulate_filtered(&mut self, chroma: f32, cos_phase: f32, sin_phase: f32) -> (f32, f32) {
+        let (first, second) = self.demodulate(chroma, cos_phase, sin_phase);
+        let first = self.first_filter.process(first);
+        let second = self.second_filter.process(second);
+        self.to_iq(first, second)
+    }
+
+    fn to_iq(&self, first: f32, second: f32) -> (f32, f32) {
+        let m = self.matrix;
+        (m[0][0] * first + m[0][1] * second, m[1][0] * first + m[1][1] * second)
+    }
+
+    /// 3-line adaptive comb. A neighbour line is trusted for combing where its luma matches
+    /// and its chroma is inverted (the subcarrier flips every line), or where both neighbours
+    /// match each other and the centre luma; where no neighbour agrees the chroma comes from
+    /// the horizontal bandpass (composite minus notch).
+    fn separate_adaptive(
+        &mut self,
+        above: Option<&DecodeLine>,
+        line: &DecodeLine,
+        below: Option<&DecodeLine>,
+        demodulation: &DemodulationConfig,
+    ) {
+        let threshold = demodulation.adaptive_threshold;
+        for x in 0..line.composite.len() {
+            let bandpass = line.composite[x] - line.luma[x];
+            let correlate = |other: Option<&DecodeLine>| match other {
+                Some(other) => (
+                    line.correlation(other, x, threshold),
+                    0.5 * (line.composite[x] - other.composite[x]),
+                ),
+                None => (0.0, 0.0),
+            };
+            // Matching lines above and below (same subcarrier phase) mean the picture is
+            // vertically uniform here, which also covers luma detail near the subcarrier.
+            let uniform = match (above, below) {
+                (Some(above), Some(below)) => {
+                    let mismatch = (above.composite[x] - below.composite[x]).abs()
+                        + (above.luma[x] - line.luma[x]).abs()
+                        + (below.luma[x] - line.luma[x]).abs();
+                    (1.0 - mismatch / threshold.max(1e-4)).clamp(0.0, 1.0)
+                }
+                _ => 0.0,
+            };
+            let (up, comb_up) = correlate(above);
+            let (down, comb_down) = correlate(below);
+            let (up, down) = (up.max(uniform), down.max(uniform));
+            let comb_weight = up.max(down);
+            let (up_share, down_share) = if up + down > 0.0 {
+                (comb_weight * up / (up + down), comb_weight * down / (up + down))
+            } else {
+                (0.0, 0.0)
+            };
+            self.adaptive_chroma[x] = up_share * comb_up + down_share * comb_down + (1.0 - comb_weight) * bandpass;
+            self.comb_blend[x] = CombBlend {
+                up: up_share,
+                down: down_share,
+                fallback: 1.0 - comb_weight,
+                temporal: 0.0,
+            };
+        }
+    }
+
+    /// Frame comb over the 2D result. The same line one frame earlier carries inverted
+    /// chroma, so static pixels separate perfectly. Motion is detected against the frame
+    /// before that (same subcarrier phase, so a still picture has identical composite) and
+    /// against the luma of the previous frame; moving pixels fade back to the adaptive 2D comb.
+    fn separate_temporal(
+        &mut self,
+        previous: Option<&DecodeLine>,
+        previous_2: Option<&DecodeLine>,
+        line: &DecodeLine,
+        demodulation: &DemodulationConfig,
+    ) {
+        let width = line.composite.len();
+        let Some(previous) = previous.filter(|previous| previous.composite.len() == width) else {
+            return;
+        };
+        let previous_2 = previous_2.filter(|previous_2| previous_2.composite.len() == width);
+        let threshold = demodulation.motion_threshold.max(1e-4);
+        for x in 0..width {
+            let motion = match previous_2 {
+                Some(previous_2) => {
+                    (line.composite[x] - previous_2.composite[x]).abs()
+                        + (line.luma[x] - previous.luma[x]).abs()
+                }
+                None => threshold * (1.0 - line.correlation(previous, x, threshold)),
+            };
+            let still = (1.0 - motion / threshold).clamp(0.0, 1.0);
+            let frame_comb = 0.5 * (line.composite[x] - previous.composite[x]);
+            self.adaptive_chroma[x] = still * frame_comb + (1.0 - still) * self.adaptive_chroma[x];
+            let blend = &mut self.comb_blend[x];
+            blend.up *= 1.0 - still;
+            blend.down *= 1.0 - still;
+            blend.fallback *= 1.0 - still;
+            blend.temporal = still;
+        }
+    }
+}
+
+impl DecodeLine {
+    /// 1 where `other` can be combed with this line at `x`: luma matches and chroma is
+    /// inverted. Falls to 0 as the mismatch reaches `threshold`.
+    fn correlation(&self, other: &DecodeLine, x: usize, threshold: f32) -> f32 {
+        let chroma = self.composite[x] - self.luma[x];
+        let other_chroma = other.composite[x] - other.luma[x];
+        let error = (other.luma[x] - self.luma[x]).abs() + (other_chroma + chroma).abs();
+        (1.0 - error / threshold.max(1e-4)).clamp(0.0, 1.0)
+    }
+}
+
+/// Input sRGB code value to the encoder matrix's domain.
+fn encode_component(value: f32, composite: &CompositeConfig) -> f32 {
+    let linear = srgb_to_linear(value.clamp(0.0, 1.0));
+    match composite.signal_domain {
+        SignalDomain::GammaEncoded => composite.camera_transfer.encode(linear),
+        SignalDomain::Linear => linear,
+    }
+}
+
+/// Decoded matrix output to an sRGB output code value, through the display in the
+/// gamma-encoded domain.
+fn display_component(value: f32, config: &PipelineConfig) -> f32 {
+    let linear = match config.composite.signal_domain {
+        SignalDomain::GammaEncoded => config.receiver.display_transfer.decode(value),
+        SignalDomain::Linear => value,
+    };
+    linear_to_srgb(linear).clamp(0.0, 1.0)
+}
+
+/// Pictures sit on the setup pedestal: black at `setup_ire`, white at 100 IRE, with the
+/// chroma scaled into the same 0..1 span.
+fn with_setup(yiq: Yiq, composite: &CompositeConfig) -> Yiq {
+    let setup = composite.setup_ire * 0.01;
+    let span = 1.0 - setup;
+    Yiq {
+        y: setup + span * yiq.y,
+        i: span * yiq.i,
+        q: span * yiq.q,
+    }
+}
+
+fn legal_range(composite: &CompositeConfig) -> (f32, f32) {
+    (composite.legal_min_ire * 0.01, composite.legal_max_ire * 0.01)
+}
+
+fn legalize(yiq: Yiq, composite: &CompositeConfig) -> Yiq {
+    if composite.legalizer != LegalizerMode::ReduceChroma {
+        return yiq;
+    }
+    let (min, max) = legal_range(composite);
+    let y = yiq.y.clamp(min, max);
+    let amplitude = yiq.i.hypot(yiq.q);
+    let allowed = (max - y).min(y - min);
+    let scale = if amplitude > allowed { allowed / amplitude } else { 1.0 };
+    Yiq {
+        y,
+        i: yiq.i * scale,
+        q: yiq.q * scale,
+    }
+}
+
+/// Input pixels whose composite excursion (luma plus or minus chroma amplitude, on the setup
+/// pedestal) leaves the legal range, before any legalizing. Row-major, one entry per pixel.
+pub fn illegal_mask(frame: &Frame, config: &PipelineConfig) -> Vec<bool> {
+    let (min, max) = legal_range(&config.composite);
+    frame
+        .data
+        .chunks_exact(3)
+        .map(|px| {
+            let [r, g, b] = [px[0], px[1], px[2]].map(|v| encode_component(v, &config.composite));
+            let yiq = with_setup(rgb_to_yiq(r, g, b), &config.composite);
+            let amplitude = yiq.i.hypot(yiq.q);
+            yiq.y + amplitude > max || yiq.y - amplitude < min
+        })
+        .collect()
 }
 
 fn encode_composite_with_phase(yiq: Yiq, phase_rad: f32) -> CompositeSample {
@@ -386,82 +1398,140 @@ fn encode_composite_with_phase(yiq: Yiq, phase_rad: f32) -> CompositeSample {
     }
 }
 
+/// Capture card ADC sampling the decoder's 4fsc grid: clock jitter on the sampling
+/// instants, the input window and a uniform quantizer.
+struct Digitizer {
+    range: (f32, f32),
+    step: f32,
+    jitter_samples: f32,
+    jitter: GaussianNoise,
+}
+
+impl Digitizer {
+    fn new(capture: &CaptureConfig, sample_rate_hz: f32, seed: u32) -> Option<Self> {
+        if !capture.enabled {
+            return None;
+        }
+        let range = (capture.input_min_ire * 0.01, capture.input_max_ire * 0.01);
+        let codes = ((1_u32 << capture.bits.clamp(1, 16)) - 1) as f32;
+        Some(Self {
+            range,
+            step: (range.1 - range.0).max(0.01) / codes,
+            jitter_samples: capture.clock_jitter_ps * 1e-12 * sample_rate_hz,
+            jitter: GaussianNoise::new(seed ^ 0x6164_6321),
+        })
+    }
+
+    fn sample_position(&mut self, position: f32) -> f32 {
+        position + self.jitter_samples * self.jitter.next_sample()
+    }
+
+    fn quantize(&self, voltage: f32) -> f32 {
+        let (low, high) = self.range;
+        low + ((voltage.clamp(low, high) - low) / self.step).round() * self.step
+    }
+}
+
+/// Averages I/Q over groups of `factor` pixels and holds the result, as a capture
+/// stored 4:2:2 or 4:1:1 and shown without chroma interpolation.
+fn subsample_chroma(line: &mut [Yiq], factor: usize) {
+    if factor <= 1 {
+        return;
+    }
+    for group in line.chunks_mut(factor) {
+        let n = group.len() as f32;
+        let i = group.iter().map(|p| p.i).sum::<f32>() / n;
+        let q = group.iter().map(|p| p.q).sum::<f32>() / n;
+        for p in group.iter_mut() {
+            p.i = i;
+            p.q = q;
+        }
+    }
+}
+
+/// Chroma leaking into luma per unit dot crawl intensity; the default 0.3 leaks 3 %.
+const CHROMA_TO_LUMA_LEAKAGE: f32 = 0.1;
+/// Luma highs leaking into I and Q per unit dot crawl intensity; the default 0.3 leaks 2 %.
+const LUMA_TO_CHROMA_LEAKAGE: f32 = 0.0667;
+
 fn decode_composite_stateful(
     sample: CompositeSample,
     cos_phase: f32,
     sin_phase: f32,
-    y_filter: &mut LowpassFilter,
-    i_filter: &mut LowpassFilter,
-    q_filter: &mut LowpassFilter,
-    i_box: &mut BoxFilter,
-    q_box: &mut BoxFilter,
-    previous_line: &mut [f32],
-    previous_line_2: &mut [f32],
     x: usize,
-    luma_highpass: &mut f32,
-    chroma_delay: &mut f32,
-    demodulation: &crate::config::DemodulationConfig,
-    artifacts: &crate::config::ArtifactConfig,
-    chroma_delay_variation: f32,
+    state: &mut DecoderState,
+    config: &PipelineConfig,
 ) -> Yiq {
-    let raw_i = sample.voltage * cos_phase;
-    let raw_q = sample.voltage * sin_phase;
+    let demodulation = &config.demodulation;
+    let artifacts = &config.artifacts;
+    let receiver = &config.receiver;
+    // Tint shifts the phase of the demodulator reference oscillator; retarding it turns
+    // the decoded hues from I towards -Q (flesh towards green).
+    let (tint_sin, tint_cos) = (-receiver.tint_deg).to_radians().sin_cos();
+    let (cos_phase, sin_phase) = (
+        cos_phase * tint_cos - sin_phase * tint_sin,
+        sin_phase * tint_cos + cos_phase * tint_sin,
+    );
 
     let (mut chroma_i, mut chroma_q, mut y) = match demodulation.filter {
         DemodulationFilter::Box => {
-            let i = i_box.process(raw_i);
-            let q = q_box.process(raw_q);
-            let y = y_filter.process(sample.voltage);
+            let (first, second) = state.demodulate(sample.voltage, cos_phase, sin_phase);
+            let first = state.first_box.process(first);
+            let second = state.second_box.process(second);
+            let (i, q) = state.to_iq(first, second);
+            let y = state.y_filter.process(sample.voltage);
             (i, q, y)
         }
         DemodulationFilter::Notch => {
-            let i = i_filter.process(raw_i);
-            let q = q_filter.process(raw_q);
+            let (i, q) = state.demodulate_filtered(sample.voltage, cos_phase, sin_phase);
             let chroma_signal = i * cos_phase + q * sin_phase;
             let notch_scale = (demodulation.notch_bandwidth_mhz / 1.5).clamp(0.1, 1.0);
-            let y = y_filter.process(sample.voltage) - demodulation.notch_depth * notch_scale * chroma_signal;
+            let y = state.y_filter.process(sample.voltage) - demodulation.notch_depth * notch_scale * chroma_signal;
             (i, q, y)
         }
         DemodulationFilter::Comb1D => {
-            let prev = previous_line[x];
+            let prev = state.previous_line[x];
             let comb_y = 0.5 * (sample.voltage + prev);
             let comb_c = 0.5 * (sample.voltage - prev) * demodulation.comb_strength;
-            previous_line[x] = sample.voltage;
-            let i = i_filter.process(comb_c * cos_phase);
-            let q = q_filter.process(comb_c * sin_phase);
-            (i, q, y_filter.process(comb_y))
+            state.previous_line[x] = sample.voltage;
+            let (i, q) = state.demodulate_filtered(comb_c, cos_phase, sin_phase);
+            (i, q, state.y_filter.process(comb_y))
         }
         DemodulationFilter::Comb2D => {
-            let prev = previous_line[x];
-            let prev2 = previous_line_2[x];
+            let prev = state.previous_line[x];
+            let prev2 = state.previous_line_2[x];
             let comb_y = (sample.voltage + prev + prev2) / 3.0;
             let comb_c = (sample.voltage - prev2) * 0.5 * demodulation.comb_strength;
-            previous_line_2[x] = prev;
-            previous_line[x] = sample.voltage;
-            let i = i_filter.process(comb_c * cos_phase);
-            let q = q_filter.process(comb_c * sin_phase);
-            (i, q, y_filter.process(comb_y))
+            state.previous_line_2[x] = prev;
+            state.previous_line[x] = sample.voltage;
+            let (i, q) = state.demodulate_filtered(comb_c, cos_phase, sin_phase);
+            (i, q, state.y_filter.process(comb_y))
+        }
+        DemodulationFilter::AdaptiveComb | DemodulationFilter::Comb3D => {
+            let comb_c = state.adaptive_chroma[x];
+            let (i, q) = state.demodulate_filtered(comb_c, cos_phase, sin_phase);
+            (i, q, state.y_filter.process(sample.voltage - comb_c))
         }
         DemodulationFilter::Lowpass => {
-            let i = i_filter.process(raw_i);
-            let q = q_filter.process(raw_q);
-            let y = y_filter.process(sample.voltage);
+            let (i, q) = state.demodulate_filtered(sample.voltage, cos_phase, sin_phase);
+            let y = state.y_filter.process(sample.voltage);
             (i, q, y)
         }
     };
 
     if artifacts.crosstalk_dynamic {
+        let leakage = config.channel.dot_crawl_intensity;
         let chroma_signal = chroma_i * cos_phase + chroma_q * sin_phase;
-        y += 0.03 * chroma_signal;
-        let high = sample.voltage - *luma_highpass;
-        *luma_highpass = sample.voltage;
-        chroma_i += 0.02 * high;
-        chroma_q += 0.02 * high;
+        y += CHROMA_TO_LUMA_LEAKAGE * leakage * chroma_signal;
+        let high = sample.voltage - state.luma_highpass;
+        state.luma_highpass = sample.voltage;
+        chroma_i += LUMA_TO_CHROMA_LEAKAGE * leakage * high;
+        chroma_q += LUMA_TO_CHROMA_LEAKAGE * leakage * high;
     }
 
     if artifacts.chroma_phase_drift_enabled {
-        *chroma_delay += chroma_delay_variation;
-        let drift = *chroma_delay;
+        state.chroma_delay += config.precision.chroma_delay_variation;
+        let drift = state.chroma_delay;
         let drift_cos = drift.cos();
         let drift_sin = drift.sin();
         let i = chroma_i * drift_cos - chroma_q * drift_sin;
@@ -470,11 +1540,27 @@ fn decode_composite_stateful(
         chroma_q = q;
     }
 
-    let i = chroma_i;
-    let q = chroma_q;
+    // The set's black level alignment takes off the pedestal and rescales to 0..1.
+    let setup = receiver.setup_ire * 0.01;
+    let span = (1.0 - setup).max(0.01);
+    let (y, chroma_i, chroma_q) = ((y - setup) / span, chroma_i / span, chroma_q / span);
+
+    // Luma peaking, the contrast (video) amplifier clipping at its headroom, then the
+    // brightness DC level; the chroma amplifier clips on its own.
+    let detail = y - state.sharpness_lowpass.process(y);
+    let y = (receiver.contrast * (y + receiver.sharpness * detail))
+        .clamp(RECEIVER_LUMA_RANGE.0, RECEIVER_LUMA_RANGE.1)
+        + receiver.brightness;
+    let chroma_gain = state.chroma_gain * receiver.contrast * receiver.color;
+    let i = (chroma_gain * chroma_i).clamp(-RECEIVER_CHROMA_CLIP, RECEIVER_CHROMA_CLIP);
+    let q = (chroma_gain * chroma_q).clamp(-RECEIVER_CHROMA_CLIP, RECEIVER_CHROMA_CLIP);
     Yiq { y, i, q }
 }
 
+const RECEIVER_PEAKING_HZ: f32 = 2_000_000.0;
+const RECEIVER_LUMA_RANGE: (f32, f32) = (-0.1, 1.2);
+const RECEIVER_CHROMA_CLIP: f32 = 0.6;
+
 fn apply_chroma_blur(i_line: &mut [f32], q_line: &mut [f32], chroma_bandwidth_mhz: f32) {
     let strength = (1.5 - chroma_bandwidth_mhz).clamp(0.0, 1.0) / 1.5;
     if strength <= 0.0 {
@@ -500,16 +1586,13 @@ fn apply_diagnostics(
     composite_line: &[f32],
     i_line: &[f32],
     q_line: &[f32],
-    resampler: &SincResampler,
     config: &PipelineConfig,
 ) {
     let width = frame.width;
     for x in 0..width {
         let idx = (y * width + x) * 3;
         if config.debug.show_composite {
-            let sample_pos = (x as f32 + 0.5) * config.precision.oversample_factor.max(1) as f32;
-            let composite = resampler.sample(composite_line, sample_pos);
-            let value = (composite * 0.5 + 0.5).clamp(0.0, 1.0);
+            let value = (composite_line[x] * 0.5 + 0.5).clamp(0.0, 1.0);
             frame.data[idx] = value;
             frame.data[idx + 1] = value;
             frame.data[idx + 2] = value;
@@ -519,7 +1602,7 @@ fn apply_diagnostics(
             frame.data[idx + 1] = (q_line[x] * 0.5 + 0.5).clamp(0.0, 1.0);
             frame.data[idx + 2] = 0.5;
         }
-        if config.debug.show_grid && (x % 16 == 0 || y % 16 == 0) {
+        if config.debug.show_grid && (x.is_multiple_of(16) || y.is_multiple_of(16)) {
             frame.data[idx] = 1.0;
             frame.data[idx + 1] = 0.1;
             frame.data[idx + 2] = 0.1;
@@ -527,6 +1610,17 @@ fn apply_diagnostics(
     }
 }
 
+/// Paints the adaptive comb decision: red = comb with line above, blue = line below,
+/// green = notch/bandpass fallback, white = frame comb.
+fn apply_comb_blend_diagnostics(frame: &mut Frame, y: usize, blend: &[CombBlend]) {
+    for (x, weights) in blend.iter().enumerate().take(frame.width) {
+        let idx = (y * frame.width + x) * 3;
+        frame.data[idx] = weights.up + weights.temporal;
+        frame.data[idx + 1] = weights.fallback + weights.temporal;
+        frame.data[idx + 2] = weights.down + weights.temporal;
+    }
+}
+
 fn apply_head_switching(
     sample: &mut CompositeSample,
     y: usize,
@@ -589,19 +1683,218 @@ fn apply_chroma_phase_drift(
     phase_rad + drift
 }
 
-fn apply_dropout(
-    sample: &mut CompositeSample,
+#[derive(Default)]
+struct DropoutState {
+    remaining: usize,
+    depth: f32,
+    delay_line: Vec<f32>,
+}
+
+fn apply_dropouts(
+    line: &mut [f32],
+    half_cycle: usize,
+    damage: &LineDamage,
+    state: &mut DropoutState,
     rng: &mut SimpleRng,
-    remaining: &mut usize,
-    artifacts: &crate::config::ArtifactConfig,
+    config: &PipelineConfig,
 ) {
-    if *remaining == 0 && rng.next_f32() < artifacts.dropout_rate {
-        let length = (artifacts.dropout_length.max(0.0) * 100.0) as usize + 1;
-        *remaining = length;
+    let artifacts = &config.artifacts;
+    let tape = &config.tape;
+    if state.delay_line.len() != line.len() {
+        state.delay_line = line.to_vec();
+    }
+    let base_rate = if artifacts.dropout_enabled {
+        artifacts.dropout_rate
+    } else {
+        0.0
+    };
+    let rate = base_rate + damage.dropout_rate;
+    let clean = line.to_vec();
+    for s in 0..line.len() {
+        if state.remaining == 0 && rng.next_f32() < rate {
+            state.remaining = (artifacts.dropout_length.max(0.0) * 100.0) as usize + 1;
+            state.depth = 0.3 + 0.7 * rng.next_f32();
+        }
+        let mut envelope = damage.envelope;
+        if state.remaining > 0 {
+            state.remaining -= 1;
+            envelope *= 1.0 - state.depth;
+        }
+        if envelope >= 1.0 {
+            continue;
+        }
+        if envelope >= tape.dropout_threshold {
+            line[s] += rng.next_signed() * 0.4 * (1.0 - envelope);
+            continue;
+        }
+        line[s] = match tape.dropout_compensation {
+            DropoutCompensation::Doc => {
+                let luma = split_luma(&state.delay_line, s, half_cycle);
+                luma + clean[s] - split_luma(&clean, s, half_cycle)
+            }
+            DropoutCompensation::WhiteSparkles => 1.0 + rng.next_f32() * 0.2,
+            DropoutCompensation::BlackSparkles => -rng.next_f32() * 0.05,
+        };
+    }
+    state.delay_line.copy_from_slice(line);
+}
+
+struct LineDamage {
+    dropout_rate: f32,
+    envelope: f32,
+    timing_shift: f32,
+}
+
+impl LineDamage {
+    fn is_active(&self) -> bool {
+        self.dropout_rate > 0.0 || self.envelope < 1.0
+    }
+}
+
+fn line_damage(events: &[TapeDamageEvent], time_s: f32, line_norm: f32) -> LineDamage {
+    let mut damage = LineDamage {
+        dropout_rate: 0.0,
+        envelope: 1.0,
+        timing_shift: 0.0,
+    };
+    for event in events.iter().filter(|event| event.is_active(time_s)) {
+        let severity = event.severity.clamp(0.0, 1.0);
+        match event.kind {
+            TapeDamageKind::Crease => {
+                let half_height = 0.02 + 0.05 * severity;
+                let weight = 1.0 - (line_norm - event.position).abs() / half_height;
+                if weight > 0.0 {
+                    damage.dropout_rate += 0.05 * severity * weight;
+                }
+            }
+            TapeDamageKind::EdgeDamage => {
+                let height = 0.05 + 0.2 * severity;
+                let weight = 1.0 - line_norm / height;
+                if weight > 0.0 {
+                    damage.dropout_rate += 0.02 * severity * weight;
+                    damage.envelope *= 1.0 - 0.6 * severity * weight;
+                }
+            }
+            TapeDamageKind::Wrinkle => {
+                let progress = (time_s - event.start_s) / event.duration_s.max(1e-3);
+                let half_height = 0.03 + 0.1 * severity;
+                let weight = 1.0 - (line_norm - progress).abs() / half_height;
+                if weight > 0.0 {
+                    damage.dropout_rate += 0.05 * severity * weight;
+                    damage.envelope *= 1.0 - severity * weight;
+                    damage.timing_shift += 8.0 * severity * weight;
+                }
+            }
+        }
     }
-    if *remaining > 0 {
-        sample.voltage += rng.next_signed() * 0.4;
-        *remaining -= 1;
+    damage
+}
+
+fn shift_line(line: &mut [f32], samples: f32) {
+    let shift = samples.round() as usize;
+    if shift == 0 || line.is_empty() {
+        return;
+    }
+    let shift = shift.min(line.len() - 1);
+    line.copy_within(..line.len() - shift, shift);
+    let fill = line[shift];
+    line[..shift].fill(fill);
+}
+
+fn split_luma(line: &[f32], s: usize, half_cycle: usize) -> f32 {
+    let last = line.len() - 1;
+    let before = line[s.saturating_sub(half_cycle)];
+    let after = line[(s + half_cycle).min(last)];
+    0.25 * before + 0.5 * line[s] + 0.25 * after
+}
+
+/// Series coupling capacitors: two first-order highpasses running over the continuous
+/// line sequence, one short enough to tilt a line and one on the scale of a field. Their
+/// state carries over between lines and frames; only the receiver clamp restores DC.
+#[derive(Default)]
+struct AcCoupling {
+    line_average: f32,
+    field_average: f32,
+}
+
+impl AcCoupling {
+    fn process(&mut self, line: &mut [f32], channel: &ChannelConfig, sample_rate_hz: f32) {
+        let alpha = |time_constant_s: f32| {
+            if time_constant_s > 0.0 {
+                1.0 - (-1.0 / (time_constant_s * sample_rate_hz)).exp()
+            } else {
+                0.0
+            }
+        };
+        let line_alpha = alpha(channel.line_coupling_us * 1e-6);
+        let field_alpha = alpha(channel.field_coupling_ms * 1e-3);
+        if line_alpha == 0.0 && field_alpha == 0.0 {
+            return;
+        }
+        for voltage in line.iter_mut() {
+            self.line_average += line_alpha * (*voltage - self.line_average);
+            let coupled = *voltage - self.line_average;
+            self.field_average += field_alpha * (coupled - self.field_average);
+            *voltage = coupled - self.field_average;
+        }
+    }
+}
+
+const YC_SPLIT_HALF_BAND_HZ: f32 = 1_300_000.0;
+
+/// Y/C misregistration: the chroma band, burst included, is split off around the
+/// subcarrier and shifted in time against luma. The burst lock takes out the carrier
+/// phase the shift adds, so only the position of the colour changes.
+struct YcDelay {
+    chroma_band: FirFilter,
+    delay_samples: f32,
+    resampler: SincResampler,
+}
+
+impl YcDelay {
+    fn new(channel: &ChannelConfig, sample_rate_hz: f32) -> Option<Self> {
+        if channel.yc_delay_ns == 0.0 {
+            return None;
+        }
+        Some(Self {
+            chroma_band: FirFilter::windowed_sinc_bandpass(
+                SUBCARRIER_HZ - YC_SPLIT_HALF_BAND_HZ,
+                SUBCARRIER_HZ + YC_SPLIT_HALF_BAND_HZ,
+                sample_rate_hz,
+            ),
+            delay_samples: channel.yc_delay_ns * 1e-9 * sample_rate_hz,
+            resampler: SincResampler::new(16),
+        })
+    }
+
+    fn apply(&self, line: &mut [f32]) {
+        let chroma = self.chroma_band.apply(line);
+        for (s, voltage) in line.iter_mut().enumerate() {
+            *voltage += self.resampler.sample(&chroma, s as f32 - self.delay_samples) - chroma[s];
+        }
+    }
+}
+
+/// DG/DP: chroma gain and subcarrier phase follow the luma level under the chroma.
+/// Luma is split off with the DOC's half-cycle comb, and the quadrature needed for the
+/// phase shift is the chroma a quarter cycle either side.
+fn apply_differential_gain_phase(line: &mut [f32], half_cycle: usize, channel: &ChannelConfig) {
+    let dg = channel.differential_gain_pct_per_ire;
+    let dp = channel.differential_phase_deg_per_ire;
+    if (dg == 0.0 && dp == 0.0) || line.is_empty() {
+        return;
+    }
+    let exponent = channel.differential_curve_exponent.max(0.1);
+    let quarter = (half_cycle / 2).max(1);
+    let last = line.len() - 1;
+    let luma: Vec<f32> = (0..line.len()).map(|s| split_luma(line, s, half_cycle)).collect();
+    let chroma: Vec<f32> = line.iter().zip(&luma).map(|(voltage, luma)| voltage - luma).collect();
+    for s in 0..line.len() {
+        let ire = 100.0 * luma[s].clamp(0.0, 1.0).powf(exponent);
+        let gain = 1.0 + dg * 0.01 * ire;
+        let (sin, cos) = (dp * ire).to_radians().sin_cos();
+        let quadrature = 0.5 * (chroma[s.saturating_sub(quarter)] - chroma[(s + quarter).min(last)]);
+        line[s] = luma[s] + gain * (chroma[s] * cos - quadrature * sin);
     }
 }
 
@@ -613,20 +1906,78 @@ fn apply_saturation(voltage: f32, artifacts: &crate::config::ArtifactConfig) ->
     }
 }
 
-fn apply_channel(sample: CompositeSample, config: &crate::config::ChannelConfig) -> CompositeSample {
-    let ringing = config.luma_ringing * (sample.phase_rad * 0.5).sin();
-    let noise = config.luma_noise * (sample.phase_rad * 13.37).cos();
+fn luma_channel_kernel(config: &ChannelConfig, sample_rate_hz: f32) -> FirFilter {
+    let ringing = config.luma_ringing.clamp(0.0, 1.0);
+    let peaking = config.luma_peaking.max(0.0);
+    let cutoff_hz = config.luma_bandwidth_mhz.max(0.1) * 1_000_000.0;
+    let mut kernel = FirFilter::windowed_sinc_lowpass(cutoff_hz, sample_rate_hz, ringing);
+    if peaking > 0.0 {
+        // Delay-line aperture corrector: y + k * (y - (y[n-d] + y[n+d]) / 2), peaking at 1/(2d).
+        let peak_hz = config.peaking_frequency_mhz.max(0.1) * 1_000_000.0;
+        let delay = (sample_rate_hz / (2.0 * peak_hz)).round().max(1.0) as usize;
+        let mut taps = vec![0.0; 2 * delay + 1];
+        taps[0] = -0.5 * peaking;
+        taps[delay] = 1.0 + peaking;
+        taps[2 * delay] = -0.5 * peaking;
+        kernel = kernel.convolve(&FirFilter {
+            taps,
+            sample_rate_hz,
+        });
+    }
+    kernel
+}
+
+const TAPE_HISS_OFF_DB: f32 = -60.0;
+
+struct NoiseSources {
+    luma: BandLimitedNoise,
+    chroma_i: BandLimitedNoise,
+    chroma_q: BandLimitedNoise,
+    hiss: PinkNoise,
+}
+
+impl NoiseSources {
+    fn new(config: &PipelineConfig, sample_rate_hz: f32, seed: u32) -> Self {
+        let luma_hz = config.channel.luma_bandwidth_mhz.max(0.1) * 1_000_000.0;
+        let chroma_hz = 500_000.0 / config.channel.color_noise_texture_scale.max(0.05);
+        Self {
+            luma: BandLimitedNoise::new(luma_hz, sample_rate_hz, seed ^ 0x6c75_6d61),
+            chroma_i: BandLimitedNoise::new(chroma_hz, sample_rate_hz, seed ^ 0x6368_7269),
+            chroma_q: BandLimitedNoise::new(chroma_hz, sample_rate_hz, seed ^ 0x6368_7271),
+            hiss: PinkNoise::new(seed ^ 0x6869_7373),
+        }
+    }
+}
+
+fn apply_channel(
+    sample: CompositeSample,
+    config: &ChannelConfig,
+    noise: &mut NoiseSources,
+) -> CompositeSample {
+    let noise = if config.luma_noise > 0.0 {
+        config.luma_noise * noise.luma.next_sample()
+    } else {
+        0.0
+    };
     CompositeSample {
-        voltage: sample.voltage + ringing + noise,
+        voltage: sample.voltage + noise,
         phase_rad: sample.phase_rad,
     }
 }
 
-fn apply_tape(sample: CompositeSample, config: &crate::config::TapeConfig) -> CompositeSample {
+fn apply_tape(
+    sample: CompositeSample,
+    config: &crate::config::TapeConfig,
+    noise: &mut NoiseSources,
+) -> CompositeSample {
     let flutter = config.flutter_depth * (sample.phase_rad * config.flutter_rate_hz).sin();
-    let dropout = if (sample.phase_rad * 0.1).sin() > 0.995 { -0.2 } else { 0.0 };
+    let hiss = if config.tape_hiss_db > TAPE_HISS_OFF_DB {
+        db_to_amplitude(config.tape_hiss_db) * noise.hiss.next_sample()
+    } else {
+        0.0
+    };
     CompositeSample {
-        voltage: sample.voltage * (1.0 - config.tracking_error) + flutter + dropout,
+        voltage: sample.voltage * (1.0 - config.tracking_error) + flutter + hiss,
         phase_rad: sample.phase_rad + config.head_switch_jitter * 0.01,
     }
 }
@@ -0,0 +1,97 @@
+//! SMPTE 12M timecode at 30 frames per second, shared by the caption and timecode readers.
+
+use std::fmt;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+const FRAMES_PER_MINUTE_DF: u64 = 1798;
+const FRAMES_PER_TEN_MINUTES_DF: u64 = 17982;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("invalid timecode")]
+pub struct ParseTimecodeError;
+
+/// 30-frame timecode; drop-frame numbering skips frames 0 and 1 of every minute not
+/// divisible by ten so it keeps pace with 29.97 fps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub drop_frame: bool,
+}
+
+impl Timecode {
+    /// Timecode of the `frame_index`-th frame from 00:00:00:00, wrapping at 24 hours.
+    pub fn from_frame_index(frame_index: u64, drop_frame: bool) -> Self {
+        let mut count = frame_index;
+        if drop_frame {
+            let tens = count / FRAMES_PER_TEN_MINUTES_DF;
+            let rest = count % FRAMES_PER_TEN_MINUTES_DF;
+            count += 18 * tens + 2 * (rest.saturating_sub(2) / FRAMES_PER_MINUTE_DF);
+        }
+        Self {
+            hours: (count / 108_000 % 24) as u8,
+            minutes: (count / 1800 % 60) as u8,
+            seconds: (count / 30 % 60) as u8,
+            frames: (count % 30) as u8,
+            drop_frame,
+        }
+    }
+
+    pub fn frame_index(&self) -> u64 {
+        let total_minutes = self.hours as u64 * 60 + self.minutes as u64;
+        let count = (total_minutes * 60 + self.seconds as u64) * 30 + self.frames as u64;
+        if self.drop_frame {
+            count - 2 * (total_minutes - total_minutes / 10)
+        } else {
+            count
+        }
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        let dropped = self.drop_frame
+            && self.seconds == 0
+            && !self.minutes.is_multiple_of(10)
+            && self.frames < 2;
+        self.hours < 24 && self.minutes < 60 && self.seconds < 60 && self.frames < 30 && !dropped
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if self.drop_frame { ';' } else { ':' };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{separator}{:02}",
+            self.hours, self.minutes, self.seconds, self.frames
+        )
+    }
+}
+
+/// Parses `HH:MM:SS:FF`, or `HH:MM:SS;FF` for drop-frame. Other separators, such as the
+/// `.` some tools write for drop-frame, are rejected.
+impl FromStr for Timecode {
+    type Err = ParseTimecodeError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<u8> = text
+            .split([':', ';'])
+            .map(|field| field.parse().ok())
+            .collect::<Option<_>>()
+            .ok_or(ParseTimecodeError)?;
+        let [hours, minutes, seconds, frames] = fields[..] else {
+            return Err(ParseTimecodeError);
+        };
+        let timecode = Self {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            drop_frame: text.contains(';'),
+        };
+        timecode.is_valid().then_some(timecode).ok_or(ParseTimecodeError)
+    }
+}
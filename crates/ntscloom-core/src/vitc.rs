@@ -0,0 +1,191 @@
+//! SMPTE 12M vertical interval timecode: the 90-bit VITC word, its line waveform and a
+//! reader that recovers timecode from a degraded line.
+
+use thiserror::Error;
+
+use crate::timecode::Timecode;
+
+/// The bit rate is 115 times the line rate, so a nominal 52.6 µs active line holds 95.3
+/// bit periods; narrower frames scale the whole waveform with their line.
+const BITS_PER_ACTIVE_LINE: f32 = 95.3;
+/// The first sync bit starts 11.2 µs after the sync edge, 1.8 µs into the active line.
+const START_BITS: f32 = 3.3;
+/// Nine groups of a `10` sync pair and eight bits: 64 data bits, then the CRC.
+const GROUPS: usize = 9;
+const GROUP_BITS: usize = 10;
+const WORD_BITS: usize = GROUPS * GROUP_BITS;
+const DATA_HIGH: f32 = 0.8;
+/// Swing below which the reader reports no signal (about half the nominal 80 IRE).
+const MIN_SWING: f32 = 0.4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum VitcError {
+    #[error("no VITC signal on the line")]
+    NoSignal,
+    #[error("VITC sync bits missing in group {0}")]
+    Sync(usize),
+    #[error("VITC CRC mismatch")]
+    Crc,
+    #[error("VITC carries an invalid timecode")]
+    InvalidTimecode,
+}
+
+/// One VITC line read back by the decoder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VitcRead {
+    /// VBI line (1-based).
+    pub line: u32,
+    pub result: Result<Timecode, VitcError>,
+}
+
+/// Running tally of VITC lines through the channel.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VitcReport {
+    pub lines_read: usize,
+    pub lines_valid: usize,
+    pub crc_failures: usize,
+    /// Lines without a signal or with missing sync bits.
+    pub sync_failures: usize,
+    /// Lines that passed CRC but disagree with the timecode that was sent.
+    pub wrong_timecodes: usize,
+}
+
+impl VitcReport {
+    pub fn record(&mut self, sent: Timecode, read: &VitcRead) {
+        self.lines_read += 1;
+        match read.result {
+            Ok(timecode) if timecode == sent => self.lines_valid += 1,
+            Ok(_) => self.wrong_timecodes += 1,
+            Err(VitcError::Crc) => self.crc_failures += 1,
+            Err(VitcError::InvalidTimecode) => self.wrong_timecodes += 1,
+            Err(VitcError::NoSignal | VitcError::Sync(_)) => self.sync_failures += 1,
+        }
+    }
+}
+
+/// The 64 data bits: BCD time with `user_bits` (eight 4-bit groups, lowest first) in
+/// between. Field mark, colour frame and binary group flags are zero.
+fn data_bits(timecode: Timecode, user_bits: u32) -> u64 {
+    let time = [
+        timecode.frames % 10,
+        (timecode.frames / 10) | ((timecode.drop_frame as u8) << 2),
+        timecode.seconds % 10,
+        timecode.seconds / 10,
+        timecode.minutes % 10,
+        timecode.minutes / 10,
+        timecode.hours % 10,
+        timecode.hours / 10,
+    ];
+    time.iter().enumerate().fold(0, |bits, (n, &digit)| {
+        let user = (user_bits >> (4 * n) & 0xf) as u64;
+        bits | (digit as u64) << (8 * n) | user << (8 * n + 4)
+    })
+}
+
+fn timecode_from_bits(bits: u64) -> Result<Timecode, VitcError> {
+    let nibble = |n: u32| (bits >> (4 * n) & 0xf) as u8;
+    let timecode = Timecode {
+        hours: nibble(14) % 4 * 10 + nibble(12),
+        minutes: nibble(10) % 8 * 10 + nibble(8),
+        seconds: nibble(6) % 8 * 10 + nibble(4),
+        frames: nibble(2) % 4 * 10 + nibble(0),
+        drop_frame: nibble(2) & 4 != 0,
+    };
+    let bcd_ok = [0, 4, 8, 12].iter().all(|&n| nibble(n) < 10);
+    if bcd_ok && timecode.is_valid() {
+        Ok(timecode)
+    } else {
+        Err(VitcError::InvalidTimecode)
+    }
+}
+
+/// CRC with generator x^8 + 1 over the 82 bits before it; the full word then divides to
+/// zero.
+fn crc(bits: &[bool]) -> u8 {
+    bits.iter()
+        .enumerate()
+        .fold(0_u8, |crc, (n, &bit)| crc ^ (bit as u8) << (n % 8))
+}
+
+/// The 90 transmitted bits for `timecode`, in line order.
+pub fn vitc_word(timecode: Timecode, user_bits: u32) -> Vec<bool> {
+    let data = data_bits(timecode, user_bits);
+    let mut bits = Vec::with_capacity(WORD_BITS);
+    for group in 0..GROUPS - 1 {
+        bits.extend([true, false]);
+        bits.extend((0..8).map(|k| data >> (group * 8 + k) & 1 == 1));
+    }
+    bits.extend([true, false]);
+    let check = crc(&bits);
+    let offset = bits.len();
+    bits.extend((0..8).map(|k| check >> ((offset + k) % 8) & 1 == 1));
+    bits
+}
+
+/// VITC active-region levels: NRZ between blanking and 80 IRE.
+pub fn vitc_waveform(timecode: Timecode, user_bits: u32, active_len: usize) -> Vec<f32> {
+    let bit_len = active_len as f32 / BITS_PER_ACTIVE_LINE;
+    let bits = vitc_word(timecode, user_bits);
+    (0..active_len)
+        .map(|s| {
+            let t = (s as f32 + 0.5) / bit_len - START_BITS;
+            match (t >= 0.0).then(|| bits.get(t as usize)).flatten() {
+                Some(true) => DATA_HIGH,
+                _ => 0.0,
+            }
+        })
+        .collect()
+}
+
+/// Reads a VITC line. The slicing level sits halfway across the line's swing; every group
+/// is retimed from the falling edge of its sync pair, as tape readers do, and the CRC
+/// checked before the timecode is decoded.
+pub fn read_vitc(active: &[f32]) -> Result<Timecode, VitcError> {
+    let bit_len = active.len() as f32 / BITS_PER_ACTIVE_LINE;
+    let high = active.iter().cloned().fold(f32::MIN, f32::max);
+    let low = active.iter().cloned().fold(f32::MAX, f32::min);
+    if active.is_empty() || high - low < MIN_SWING {
+        return Err(VitcError::NoSignal);
+    }
+    let threshold = 0.5 * (high + low);
+    let level_at = |centre: f32| -> Option<bool> {
+        let window = (centre - 0.25 * bit_len).max(0.0) as usize
+            ..((centre + 0.25 * bit_len) as usize + 1).min(active.len());
+        (!window.is_empty()).then(|| {
+            active[window.clone()].iter().sum::<f32>() / window.len() as f32 > threshold
+        })
+    };
+
+    let mut bits = Vec::with_capacity(WORD_BITS);
+    let mut expected_edge = (START_BITS + 1.0) * bit_len;
+    for group in 0..GROUPS {
+        let search = (expected_edge - bit_len).max(1.0) as usize
+            ..((expected_edge + bit_len) as usize).min(active.len());
+        let edge = search
+            .filter(|&s| active[s - 1] >= threshold && active[s] < threshold)
+            .min_by_key(|&s| (s as f32 - expected_edge).abs() as usize)
+            .ok_or(VitcError::Sync(group))? as f32;
+        if level_at(edge - 0.5 * bit_len) != Some(true) {
+            return Err(VitcError::Sync(group));
+        }
+        bits.extend([true, false]);
+        for k in 0..8 {
+            bits.push(level_at(edge + (k as f32 + 1.5) * bit_len).ok_or(VitcError::Sync(group))?);
+        }
+        expected_edge = edge + GROUP_BITS as f32 * bit_len;
+    }
+
+    let check = crc(&bits[..WORD_BITS - 8]);
+    let sent: u8 = (0..8)
+        .map(|k| (bits[WORD_BITS - 8 + k] as u8) << ((WORD_BITS - 8 + k) % 8))
+        .fold(0, |crc, bit| crc | bit);
+    if check != sent {
+        return Err(VitcError::Crc);
+    }
+    let data = (0..GROUPS - 1)
+        .flat_map(|group| (0..8).map(move |k| (group, k)))
+        .fold(0_u64, |data, (group, k)| {
+            data | (bits[group * GROUP_BITS + 2 + k] as u64) << (group * 8 + k)
+        });
+    timecode_from_bits(data)
+}
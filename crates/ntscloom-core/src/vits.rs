@@ -0,0 +1,332 @@
+//! Vertical interval test signals (multiburst, modulated staircase, 2T pulse and bar, and
+//! an NTC-7 style composite line) and the waveform-monitor measurements taken on them
+//! after the channel.
+
+use std::f32::consts::{PI, TAU};
+
+use crate::dsp::{Yiq, SUBCARRIER_HZ};
+use crate::pipeline::BURST_IQ;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VitsSignal {
+    Multiburst,
+    ModulatedStaircase,
+    PulseAndBar,
+    Composite,
+}
+
+/// FCC multiburst packet frequencies.
+pub const MULTIBURST_MHZ: [f32; 6] = [0.5, 1.25, 2.0, 3.0, 3.58, 4.1];
+/// Packets are 60 IRE p-p on a 40 IRE pedestal, after a 70 IRE flag.
+const MULTIBURST_AMPLITUDE: f32 = 0.3;
+const MULTIBURST_PEDESTAL: f32 = 0.4;
+const MULTIBURST_FLAG: f32 = 0.7;
+const FLAG: Span = Span(0.02, 0.10);
+const PEDESTAL: Span = Span(0.12, 0.96);
+const PACKET_START: f32 = 0.13;
+const PACKET_PITCH: f32 = 0.14;
+const PACKET_LEN: f32 = 0.12;
+
+/// Six 20 IRE steps, each carrying 20 IRE p-p subcarrier at burst phase.
+const STAIRCASE_STEPS: usize = 6;
+const STAIRCASE_RISER: f32 = 0.2;
+const STAIRCASE_CHROMA: f32 = 0.1;
+
+const T_S: f32 = 125e-9;
+const BAR_LEVEL: f32 = 1.0;
+/// The 12.5T pulse splits its 100 IRE between a luma sin² and a chroma envelope.
+const MODULATED_PULSE_HALF: f32 = 0.5;
+
+/// Layouts in fractions of the active line. Narrow frames shorten the line, so the low
+/// multiburst packets need roughly 640 pixels or more to hold whole cycles.
+const STAIRCASE: Span = Span(0.08, 0.92);
+const PULSE_2T_AT: f32 = 0.15;
+const BAR: Span = Span(0.3, 0.8);
+const COMPOSITE_BAR: Span = Span(0.04, 0.24);
+const COMPOSITE_2T_AT: f32 = 0.3;
+const COMPOSITE_12T_AT: f32 = 0.4;
+const COMPOSITE_STAIRCASE: Span = Span(0.5, 0.94);
+
+/// Start and end of a segment, as fractions of the active line.
+#[derive(Clone, Copy)]
+struct Span(f32, f32);
+
+impl Span {
+    fn contains(&self, frac: f32) -> bool {
+        (self.0..self.1).contains(&frac)
+    }
+
+    /// Sample range of the middle `portion` of the span.
+    fn centre(&self, portion: f32, len: usize) -> std::ops::Range<usize> {
+        let margin = 0.5 * (1.0 - portion) * (self.1 - self.0);
+        ((self.0 + margin) * len as f32) as usize..((self.1 - margin) * len as f32) as usize
+    }
+
+    fn steps(&self, count: usize) -> impl Iterator<Item = Span> + '_ {
+        let pitch = (self.1 - self.0) / count as f32;
+        (0..count).map(move |k| Span(self.0 + k as f32 * pitch, self.0 + (k + 1) as f32 * pitch))
+    }
+}
+
+fn packet(k: usize) -> Span {
+    let start = PACKET_START + k as f32 * PACKET_PITCH;
+    Span(start, start + PACKET_LEN)
+}
+
+/// Subcarrier I/Q of unit amplitude at burst phase.
+fn burst_phase() -> (f32, f32) {
+    let norm = BURST_IQ.0.hypot(BURST_IQ.1);
+    (BURST_IQ.0 / norm, BURST_IQ.1 / norm)
+}
+
+/// sin² pulse with half-amplitude duration `had_s`, `t_s` from its centre.
+fn sin2(t_s: f32, had_s: f32) -> f32 {
+    if t_s.abs() < had_s {
+        (0.5 * PI * t_s / had_s).cos().powi(2)
+    } else {
+        0.0
+    }
+}
+
+/// Bar with sin²-shaped 2T edges.
+fn bar(t_s: f32, start_s: f32, end_s: f32) -> f32 {
+    let edge = 2.0 * T_S;
+    let rise = ((t_s - start_s) / edge + 0.5).clamp(0.0, 1.0);
+    let fall = ((end_s - t_s) / edge + 0.5).clamp(0.0, 1.0);
+    BAR_LEVEL * (0.5 - 0.5 * (PI * rise.min(fall)).cos())
+}
+
+fn staircase(span: Span, frac: f32) -> Yiq {
+    let (ci, cq) = burst_phase();
+    match span.steps(STAIRCASE_STEPS).position(|step| step.contains(frac)) {
+        Some(k) => Yiq {
+            y: k as f32 * STAIRCASE_RISER,
+            i: STAIRCASE_CHROMA * ci,
+            q: STAIRCASE_CHROMA * cq,
+        },
+        None => Yiq { y: 0.0, i: 0.0, q: 0.0 },
+    }
+}
+
+/// Active-region samples of `signal` at `sample_rate_hz`, levels from blanking.
+pub fn vits_line(signal: VitsSignal, active_len: usize, sample_rate_hz: f32) -> Vec<Yiq> {
+    let line_s = active_len as f32 / sample_rate_hz;
+    let (ci, cq) = burst_phase();
+    (0..active_len)
+        .map(|s| {
+            let frac = s as f32 / active_len as f32;
+            let t_s = s as f32 / sample_rate_hz;
+            match signal {
+                VitsSignal::Multiburst => {
+                    let y = if FLAG.contains(frac) {
+                        MULTIBURST_FLAG
+                    } else if PEDESTAL.contains(frac) {
+                        let burst = (0..MULTIBURST_MHZ.len()).find(|&k| packet(k).contains(frac));
+                        MULTIBURST_PEDESTAL
+                            + burst.map_or(0.0, |k| {
+                                let start_s = packet(k).0 * line_s;
+                                MULTIBURST_AMPLITUDE
+                                    * (TAU * MULTIBURST_MHZ[k] * 1e6 * (t_s - start_s)).sin()
+                            })
+                    } else {
+                        0.0
+                    };
+                    Yiq { y, i: 0.0, q: 0.0 }
+                }
+                VitsSignal::ModulatedStaircase => staircase(STAIRCASE, frac),
+                VitsSignal::PulseAndBar => Yiq {
+                    y: BAR_LEVEL * sin2(t_s - PULSE_2T_AT * line_s, 2.0 * T_S)
+                        + bar(t_s, BAR.0 * line_s, BAR.1 * line_s),
+                    i: 0.0,
+                    q: 0.0,
+                },
+                VitsSignal::Composite => {
+                    if COMPOSITE_STAIRCASE.contains(frac) {
+                        return staircase(COMPOSITE_STAIRCASE, frac);
+                    }
+                    let envelope =
+                        MODULATED_PULSE_HALF * sin2(t_s - COMPOSITE_12T_AT * line_s, 12.5 * T_S);
+                    Yiq {
+                        y: BAR_LEVEL * sin2(t_s - COMPOSITE_2T_AT * line_s, 2.0 * T_S)
+                            + bar(t_s, COMPOSITE_BAR.0 * line_s, COMPOSITE_BAR.1 * line_s)
+                            + envelope,
+                        i: envelope * ci,
+                        q: envelope * cq,
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// Waveform-monitor readings from the VITS lines of the last frame. Readings whose test
+/// signal is not on any line stay empty.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VitsMeasurement {
+    /// Multiburst packet amplitude relative to what was sent, as (MHz, dB).
+    pub frequency_response: Vec<(f32, f32)>,
+    /// Peak-to-peak spread of staircase chroma amplitude, relative to the blanking step.
+    pub differential_gain_pct: Option<f32>,
+    /// Peak-to-peak spread of staircase chroma phase.
+    pub differential_phase_deg: Option<f32>,
+    /// 2T pulse-to-bar K-factor, `|B - P| / 4P`.
+    pub k_pulse_bar_pct: Option<f32>,
+    /// Bar droop from its first to its last quarter, relative to the bar.
+    pub bar_tilt_pct: Option<f32>,
+    /// 12.5T chroma pulse: chroma envelope peak over luma peak.
+    pub chroma_luma_gain_pct: Option<f32>,
+}
+
+impl VitsMeasurement {
+    /// Fills readings missing here from `other`: the dedicated lines take precedence over
+    /// the composite line.
+    pub(crate) fn or(self, other: VitsMeasurement) -> Self {
+        Self {
+            frequency_response: if self.frequency_response.is_empty() {
+                other.frequency_response
+            } else {
+                self.frequency_response
+            },
+            differential_gain_pct: self.differential_gain_pct.or(other.differential_gain_pct),
+            differential_phase_deg: self.differential_phase_deg.or(other.differential_phase_deg),
+            k_pulse_bar_pct: self.k_pulse_bar_pct.or(other.k_pulse_bar_pct),
+            bar_tilt_pct: self.bar_tilt_pct.or(other.bar_tilt_pct),
+            chroma_luma_gain_pct: self.chroma_luma_gain_pct.or(other.chroma_luma_gain_pct),
+        }
+    }
+}
+
+/// Received active region with the decoder's burst-locked subcarrier alongside.
+pub(crate) struct VitsInput<'a> {
+    pub line: &'a [f32],
+    pub cos_line: &'a [f32],
+    pub sin_line: &'a [f32],
+    pub sample_rate_hz: f32,
+}
+
+impl VitsInput<'_> {
+    fn mean(&self, range: std::ops::Range<usize>) -> f32 {
+        let len = range.len().max(1) as f32;
+        self.line[range].iter().sum::<f32>() / len
+    }
+
+    /// Subcarrier I/Q over `range`, trimmed to whole subcarrier cycles, DC removed.
+    fn chroma(&self, range: std::ops::Range<usize>) -> (f32, f32) {
+        let cycle = (self.sample_rate_hz / SUBCARRIER_HZ).round().max(1.0) as usize;
+        let range = range.start..range.start + (range.len() / cycle).max(1) * cycle;
+        let range = range.start..range.end.min(self.line.len());
+        let dc = self.mean(range.clone());
+        let (i, q) = range.clone().fold((0.0, 0.0), |(i, q), s| {
+            let v = self.line[s] - dc;
+            (i + v * self.cos_line[s], q + v * self.sin_line[s])
+        });
+        let scale = 2.0 / range.len().max(1) as f32;
+        (i * scale, q * scale)
+    }
+
+    /// Amplitude of the `hz` component over the whole cycles of `range`.
+    fn tone(&self, range: std::ops::Range<usize>, hz: f32) -> f32 {
+        let period = self.sample_rate_hz / hz;
+        let cycles = (range.len() as f32 / period).floor().max(1.0);
+        let end = range.start + (cycles * period).round() as usize;
+        let range = range.start..end.min(self.line.len());
+        let dc = self.mean(range.clone());
+        let (c, s) = range.clone().fold((0.0, 0.0), |(c, s), n| {
+            let phase = TAU * n as f32 / period;
+            let v = self.line[n] - dc;
+            (c + v * phase.cos(), s + v * phase.sin())
+        });
+        2.0 * c.hypot(s) / range.len().max(1) as f32
+    }
+
+    fn at(&self, frac: f32) -> usize {
+        ((frac * self.line.len() as f32) as usize).min(self.line.len().saturating_sub(1))
+    }
+
+    fn samples(&self, seconds: f32) -> usize {
+        (seconds * self.sample_rate_hz).round() as usize
+    }
+
+    fn staircase(&self, span: Span, into: &mut VitsMeasurement) {
+        let len = self.line.len();
+        let steps: Vec<(f32, f32)> = span
+            .steps(STAIRCASE_STEPS)
+            .map(|step| {
+                let (i, q) = self.chroma(step.centre(0.6, len));
+                (i.hypot(q), q.atan2(i))
+            })
+            .collect();
+        let (reference, reference_phase) = steps[0];
+        let gains = steps.iter().map(|(amplitude, _)| amplitude / reference.max(1e-6));
+        let phases = steps.iter().map(|(_, phase)| {
+            let delta = (phase - reference_phase + PI).rem_euclid(TAU) - PI;
+            delta.to_degrees()
+        });
+        let spread = |values: Vec<f32>| {
+            let max = values.iter().cloned().fold(f32::MIN, f32::max);
+            let min = values.iter().cloned().fold(f32::MAX, f32::min);
+            max - min
+        };
+        into.differential_gain_pct = Some(100.0 * spread(gains.collect()));
+        into.differential_phase_deg = Some(spread(phases.collect()));
+    }
+
+    fn pulse_and_bar(&self, pulse_at: f32, bar_span: Span, into: &mut VitsMeasurement) {
+        let had = self.samples(2.0 * T_S).max(1);
+        let centre = self.at(pulse_at);
+        let baseline =
+            self.mean(centre.saturating_sub(3 * had)..centre.saturating_sub(2 * had));
+        let pulse_window = centre.saturating_sub(had)..(centre + had).min(self.line.len());
+        let peak = self.line[pulse_window].iter().cloned().fold(f32::MIN, f32::max) - baseline;
+        let start = self.at(bar_span.0);
+        let bar_baseline = self.mean(start.saturating_sub(3 * had)..start.saturating_sub(2 * had));
+        let len = self.line.len();
+        let bar = self.mean(bar_span.centre(0.5, len)) - bar_baseline;
+        let quarters: Vec<Span> = bar_span.steps(4).collect();
+        let early = self.mean(quarters[0].centre(0.5, len));
+        let late = self.mean(quarters[3].centre(0.5, len));
+        if peak > 0.0 && bar > 0.0 {
+            into.k_pulse_bar_pct = Some(25.0 * (bar - peak).abs() / peak);
+            into.bar_tilt_pct = Some(100.0 * (early - late) / bar);
+        }
+    }
+
+    fn modulated_pulse(&self, pulse_at: f32, into: &mut VitsMeasurement) {
+        let cycle = self.samples(1.0 / SUBCARRIER_HZ).max(1);
+        let centre = self.at(pulse_at);
+        let had = self.samples(12.5 * T_S);
+        let before = centre.saturating_sub(had);
+        let baseline = self.mean(before.saturating_sub(2 * cycle)..before);
+        let peak = centre.saturating_sub(cycle / 2)..centre + cycle - cycle / 2;
+        let luma = self.mean(peak.clone()) - baseline;
+        let (i, q) = self.chroma(peak);
+        if luma > 0.0 {
+            into.chroma_luma_gain_pct = Some(100.0 * i.hypot(q) / luma);
+        }
+    }
+
+    fn multiburst(&self, into: &mut VitsMeasurement) {
+        into.frequency_response = MULTIBURST_MHZ
+            .iter()
+            .enumerate()
+            .map(|(k, &mhz)| {
+                let amplitude = self.tone(packet(k).centre(0.8, self.line.len()), mhz * 1e6);
+                (mhz, 20.0 * (amplitude.max(1e-6) / MULTIBURST_AMPLITUDE).log10())
+            })
+            .collect();
+    }
+
+    /// Sets the readings `signal` provides.
+    pub fn measure(&self, signal: VitsSignal, into: &mut VitsMeasurement) {
+        match signal {
+            VitsSignal::Multiburst => self.multiburst(into),
+            VitsSignal::ModulatedStaircase => self.staircase(STAIRCASE, into),
+            VitsSignal::PulseAndBar => self.pulse_and_bar(PULSE_2T_AT, BAR, into),
+            VitsSignal::Composite => {
+                self.pulse_and_bar(COMPOSITE_2T_AT, COMPOSITE_BAR, into);
+                self.modulated_pulse(COMPOSITE_12T_AT, into);
+                self.staircase(COMPOSITE_STAIRCASE, into);
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use ntscloom_core::captions::{with_parity, NULL_PAIR};
+use ntscloom_core::{CaptionTrack, Frame, Pipeline, PipelineConfig};
+
+#[test]
+fn scc_and_srt_files_become_parity_coded_pairs_on_their_frames() {
+    let scc = "Scenarist_SCC V1.0\n\n00:00:00:02\t9420 9420\n\n00:00:00:03\t94ae\n";
+    let track = CaptionTrack::from_scc(scc).unwrap();
+    // The second line's timecode falls inside the first line's data, so it follows on.
+    assert_eq!(
+        track.pairs(),
+        &[NULL_PAIR, NULL_PAIR, [0x94, 0x20], [0x94, 0x20], [0x94, 0xae]]
+    );
+    let drop_frame = CaptionTrack::from_scc("00:01:00;02\t9420\n").unwrap();
+    assert_eq!(drop_frame.pairs().len(), 1801, "drop-frame timecode skips two frame numbers");
+    assert!(CaptionTrack::from_scc("00:00:00:00\t94\n").is_err());
+    assert!(CaptionTrack::from_scc("00:01:00;00\t9420\n").is_err(), "dropped frame number");
+    assert!(CaptionTrack::from_scc("00:00:00.02\t9420\n").is_err());
+
+    let srt = "1\n00:00:01,000 --> 00:00:02,000\nHi!\n";
+    let track = CaptionTrack::from_srt(srt).unwrap();
+    let end_of_caption = [0x94, 0x2f];
+    assert_eq!(track.pair(29), end_of_caption, "caption pops on at the cue start");
+    assert_eq!(track.pair(28), end_of_caption, "control codes are doubled");
+    assert_eq!(track.pair(24), [with_parity(b'H'), with_parity(b'i')]);
+    assert_eq!(track.pair(25), [with_parity(b'!'), 0x80]);
+    assert_eq!(track.pair(17), NULL_PAIR);
+    assert_eq!(track.pair(60), [0x94, 0x2c], "erased at the cue end");
+    assert_eq!(track.pair(1000), NULL_PAIR);
+    for byte in track.pairs().iter().flatten() {
+        assert_eq!(byte.count_ones() % 2, 1, "odd parity on {byte:#04x}");
+    }
+}
+
+#[test]
+fn back_to_back_srt_cues_each_pop_on_at_their_start() {
+    let srt = "1\n00:00:01,000 --> 00:00:02,000\nHi!\n\n2\n00:00:02,000 --> 00:00:03,000\nYo\n\n\
+               3\n00:00:04,000 --> 00:00:05,000\nOk\n";
+    let track = CaptionTrack::from_srt(srt).unwrap();
+    let (end_of_caption, erase) = ([0x94, 0x2f], [0x94, 0x2c]);
+    assert_eq!(track.pair(29), end_of_caption);
+    // The second cue loads while the first is on screen and replaces it on frame 60.
+    assert_eq!(track.pair(59), end_of_caption, "second cue pops on at its start");
+    assert_eq!(track.pair(58), end_of_caption);
+    assert_ne!(track.pair(60), erase, "no erase right after the second cue pops on");
+    // A gap before the third cue leaves room for the second one's erase.
+    assert_eq!(track.pair(90), erase, "erased at the cue end");
+    assert_eq!(track.pair(119), end_of_caption, "third cue pops on at its start");
+    assert_eq!(track.pair(150), erase);
+}
+
+fn quiet_config() -> PipelineConfig {
+    let mut config = PipelineConfig::default();
+    config.channel.luma_ringing = 0.0;
+    config.channel.luma_noise = 0.0;
+    config.tape.flutter_depth = 0.0;
+    config.tape.tracking_error = 0.0;
+    config.artifacts.head_switch_enabled = false;
+    config.artifacts.vertical_jitter_enabled = false;
+    config.artifacts.horizontal_tbc_enabled = false;
+    config.artifacts.chroma_phase_drift_enabled = false;
+    config.artifacts.dropout_enabled = false;
+    config.artifacts.saturation_enabled = false;
+    config.precision.pll_phase_noise = 0.0;
+    config
+}
+
+#[test]
+fn line21_captions_survive_a_clean_channel_and_break_up_in_noise() {
+    let pairs: Vec<[u8; 2]> = (0..10)
+        .map(|n| [with_parity(b'A' + n), with_parity(b'a' + n)])
+        .collect();
+    let frame = Frame::new(160, 120);
+    let run = |config: PipelineConfig| {
+        let mut pipeline = Pipeline::new(config, 14_318_180.0);
+        pipeline.set_captions(Some(CaptionTrack::from_pairs(pairs.clone())));
+        for _ in 0..pairs.len() {
+            pipeline.process(&frame);
+        }
+        pipeline.caption_report()
+    };
+
+    let clean = run(quiet_config());
+    assert_eq!(clean.frames, 10);
+    assert_eq!(clean.bytes_sent, 20);
+    assert_eq!(clean.bytes_intact, 20, "{clean:?}");
+
+    let mut noisy = quiet_config();
+    noisy.channel.luma_noise = 0.5;
+    let noisy = run(noisy);
+    assert!(noisy.survival() < 0.5, "{noisy:?}");
+    assert!(noisy.parity_errors > 0, "parity catches some of the damage: {noisy:?}");
+    assert_eq!(
+        noisy.bytes_intact + noisy.parity_errors + noisy.undetected_errors + 2 * noisy.lines_lost,
+        noisy.bytes_sent
+    );
+}
+
+#[test]
+fn captions_set_after_a_seek_start_from_their_first_pair() {
+    let pairs = vec![[with_parity(b'H'), with_parity(b'i')], [0x94, 0x2f]];
+    let frame = Frame::new(160, 120);
+    let mut pipeline = Pipeline::new(quiet_config(), 14_318_180.0);
+    pipeline.seek(300);
+    pipeline.set_captions(Some(CaptionTrack::from_pairs(pairs.clone())));
+    for pair in pairs.iter().chain([&NULL_PAIR]) {
+        pipeline.process(&frame);
+        assert_eq!(pipeline.caption_read(), Some(*pair));
+    }
+    assert_eq!(pipeline.caption_report().bytes_intact, 6);
+
+    pipeline.seek(299);
+    pipeline.process(&frame);
+    assert_eq!(pipeline.caption_read(), None, "no captions before the track started");
+}
@@ -0,0 +1,138 @@
+use ntscloom_core::dsp::srgb_to_linear;
+use ntscloom_core::{process_frame, Frame, PhosphorMask, Pipeline, PipelineConfig};
+
+fn crt_config(mask: PhosphorMask) -> PipelineConfig {
+    let mut config = PipelineConfig::default();
+    config.channel.luma_noise = 0.0;
+    config.tape.flutter_depth = 0.0;
+    config.tape.tracking_error = 0.0;
+    config.artifacts.head_switch_enabled = false;
+    config.artifacts.vertical_jitter_enabled = false;
+    config.artifacts.horizontal_tbc_enabled = false;
+    config.artifacts.chroma_phase_drift_enabled = false;
+    config.artifacts.dropout_enabled = false;
+    config.precision.pll_phase_noise = 0.0;
+    config.crt.enabled = true;
+    config.crt.mask = mask;
+    config.crt.halation = 0.0;
+    config.crt.bloom = 0.0;
+    config
+}
+
+fn flat(level: f32) -> Frame {
+    let mut frame = Frame::new(64, 48);
+    frame.data.fill(level);
+    frame
+}
+
+/// Mean linear light of `channel` over the output pixels selected by `keep(x, y)`.
+fn mean_light(out: &Frame, channel: usize, keep: impl Fn(usize, usize) -> bool) -> f32 {
+    let (mut sum, mut count) = (0.0, 0);
+    for y in out.height / 4..out.height * 3 / 4 {
+        for x in out.width / 4..out.width * 3 / 4 {
+            if keep(x, y) {
+                sum += srgb_to_linear(out.data[(y * out.width + x) * 3 + channel]);
+                count += 1;
+            }
+        }
+    }
+    sum / count as f32
+}
+
+#[test]
+fn crt_upscales_with_scanline_gaps_that_close_as_the_spot_grows() {
+    let config = crt_config(PhosphorMask::None);
+    let gap_ratio = |level: f32| {
+        let out = process_frame(&flat(level), &config, 14_318_180.0);
+        assert_eq!((out.width, out.height), (192, 144));
+        // Row 3k + 1 is on a scanline's centre, row 3k + 2 a third of a line off it.
+        let between = mean_light(&out, 1, |_, y| y % 3 == 2);
+        between / mean_light(&out, 1, |_, y| y % 3 == 1)
+    };
+    let dark = gap_ratio(0.25);
+    let bright = gap_ratio(0.95);
+    assert!(dark < 0.8, "a dim, narrow spot leaves gaps between the lines ({dark})");
+    assert!(bright > dark + 0.15, "a bright spot blooms over the gaps ({dark} vs {bright})");
+
+    let source = flat(0.6);
+    let once = process_frame(&source, &config, 14_318_180.0);
+    assert_eq!(once.data, process_frame(&source, &config, 14_318_180.0).data);
+}
+
+#[test]
+fn phosphor_masks_split_white_into_triads_and_glow_spreads_highlights() {
+    let mut config = crt_config(PhosphorMask::ApertureGrille);
+    config.crt.mask_strength = 1.0;
+    let out = process_frame(&flat(1.0), &config, 14_318_180.0);
+    for channel in 0..3 {
+        let own = mean_light(&out, channel, |x, _| x % 3 == channel);
+        let other = mean_light(&out, channel, |x, _| x % 3 != channel);
+        assert!(own > 0.5 && other < 0.01, "stripe {channel}: {own} vs {other}");
+    }
+
+    config.crt.mask = PhosphorMask::SlotMask;
+    let out = process_frame(&flat(1.0), &config, 14_318_180.0);
+    let rib = mean_light(&out, 0, |x, y| x % 6 == 0 && y % 6 == 5);
+    assert!(rib < 0.01, "slot mask ribs cut the stripes ({rib})");
+    // Below clipping the tube is driven harder to make up for the mask.
+    let mut average = |mask: PhosphorMask| {
+        config.crt.mask = mask;
+        let out = process_frame(&flat(0.3), &config, 14_318_180.0);
+        mean_light(&out, 1, |_, _| true)
+    };
+    let (open, shadow) = (average(PhosphorMask::None), average(PhosphorMask::ShadowMask));
+    assert!((shadow / open - 1.0).abs() < 0.1, "shadow mask {shadow} vs no mask {open}");
+
+    let mut frame = Frame::new(64, 48);
+    for y in 20..28 {
+        for x in 28..36 {
+            frame.data[(y * 64 + x) * 3..][..3].fill(1.0);
+        }
+    }
+    let mut config = crt_config(PhosphorMask::None);
+    let beside = |config: &PipelineConfig| {
+        let out = process_frame(&frame, config, 14_318_180.0);
+        // Four input pixels to the right of the box, on its middle line.
+        srgb_to_linear(out.data[(72 * out.width + 3 * 40) * 3 + 1])
+    };
+    let plain = beside(&config);
+    config.crt.halation = 0.1;
+    config.crt.bloom = 0.3;
+    let glowing = beside(&config);
+    assert!(glowing > plain + 0.005, "glow lights the surround ({plain} vs {glowing})");
+}
+
+#[test]
+fn phosphor_persistence_leaves_per_channel_trails_and_blends_fields() {
+    let mut config = crt_config(PhosphorMask::None);
+    config.crt.scale = 1;
+    config.crt.phosphor_decay_frames = [0.5, 0.2, 0.0];
+    let still = process_frame(&flat(0.6), &config, 14_318_180.0);
+    let mut instant = config.clone();
+    instant.crt.phosphor_decay_frames = [0.0; 3];
+    let reference = process_frame(&flat(0.6), &instant, 14_318_180.0);
+    let worst = still.data.iter().zip(&reference.data).map(|(a, b)| (a - b).abs());
+    assert!(worst.fold(0.0, f32::max) < 1e-3, "a still picture looks the same");
+
+    let mut pipeline = Pipeline::new(config, 14_318_180.0);
+    pipeline.process(&flat(1.0));
+    let after = pipeline.process(&flat(0.0));
+    let trail = |channel: usize, field: usize| mean_light(&after, channel, |_, y| y % 2 == field);
+    let (red, green, blue) = (trail(0, 0), trail(1, 0), trail(2, 0));
+    assert!(red > green && green > blue, "slower phosphors glow longer ({red}, {green}, {blue})");
+    assert!(blue < 0.01, "a phosphor without persistence is dark at once ({blue})");
+    assert!(trail(0, 1) > red, "the later field spills more into the next frame");
+}
+
+#[test]
+fn mask_gains_follow_a_mask_change_between_frames() {
+    let shadow = crt_config(PhosphorMask::ShadowMask);
+    let second_frame = |first: PipelineConfig| {
+        let mut pipeline = Pipeline::new(first, 14_318_180.0);
+        pipeline.process(&flat(0.3));
+        pipeline.set_config(shadow.clone());
+        pipeline.process(&flat(0.3))
+    };
+    let switched = second_frame(crt_config(PhosphorMask::ApertureGrille));
+    assert_eq!(switched.data, second_frame(shadow.clone()).data);
+}
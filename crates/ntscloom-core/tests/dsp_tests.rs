@@ -0,0 +1,140 @@
+use ntscloom_core::dsp::filter::{
+    FilterPrototype, FilterSpec, FirFilter, FrequencyResponse, MAX_ORDER,
+};
+use ntscloom_core::dsp::noise::{BandLimitedNoise, GaussianNoise, PinkNoise};
+
+fn stats(samples: &[f32]) -> (f32, f32) {
+    let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+    let variance = samples.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / samples.len() as f32;
+    (mean, variance)
+}
+
+fn lag_correlation(samples: &[f32], lag: usize) -> f32 {
+    let (mean, variance) = stats(samples);
+    let sum: f32 = samples
+        .windows(lag + 1)
+        .map(|w| (w[0] - mean) * (w[lag] - mean))
+        .sum();
+    sum / (samples.len() - lag) as f32 / variance
+}
+
+#[test]
+fn noise_generators_are_unit_variance_with_expected_colour() {
+    let mut gaussian = GaussianNoise::new(7);
+    let mut pink = PinkNoise::new(7);
+    let mut band = BandLimitedNoise::new(500_000.0, 28_636_360.0, 7);
+    let white: Vec<f32> = (0..200_000).map(|_| gaussian.next_sample()).collect();
+    let pink: Vec<f32> = (0..200_000).map(|_| pink.next_sample()).collect();
+    let band: Vec<f32> = (0..200_000).map(|_| band.next_sample()).collect();
+    for (name, samples) in [("white", &white), ("pink", &pink), ("band", &band)] {
+        let (mean, variance) = stats(samples);
+        assert!(mean.abs() < 0.1, "{name} mean {mean}");
+        assert!((variance - 1.0).abs() < 0.15, "{name} variance {variance}");
+    }
+    assert!(lag_correlation(&white, 8).abs() < 0.02);
+    assert!(lag_correlation(&pink, 8) > 0.3);
+    assert!(lag_correlation(&band, 8) > 0.3);
+}
+
+const FS: f32 = 28_636_360.0;
+
+fn measured_gain(spec: &FilterSpec, freq_hz: f32) -> f32 {
+    let mut filter = spec.design(FS);
+    let w = 2.0 * std::f32::consts::PI * freq_hz / FS;
+    let mut peak = 0.0_f32;
+    for n in 0..20_000 {
+        let out = filter.process((w * n as f32).sin());
+        if n > 10_000 {
+            peak = peak.max(out.abs());
+        }
+    }
+    peak
+}
+
+#[test]
+fn prototypes_hit_their_cutoff_and_order_sets_slope() {
+    for prototype in [
+        FilterPrototype::Butterworth,
+        FilterPrototype::Bessel,
+        FilterPrototype::Gaussian,
+    ] {
+        for order in 1..=MAX_ORDER {
+            let filter = FilterSpec::new(prototype, order, 1_000_000.0).design(FS);
+            let at_cutoff = filter.magnitude_db(1_000_000.0);
+            assert!(
+                (at_cutoff + 3.01).abs() < 0.1,
+                "{prototype:?} order {order}: {at_cutoff} dB at cutoff"
+            );
+            assert!((filter.magnitude(0.0) - 1.0).abs() < 1e-4);
+        }
+    }
+    let second = FilterSpec::new(FilterPrototype::Butterworth, 2, 1_000_000.0).design(FS);
+    let fourth = FilterSpec::new(FilterPrototype::Butterworth, 4, 1_000_000.0).design(FS);
+    assert!(fourth.magnitude_db(4_000_000.0) < second.magnitude_db(4_000_000.0) - 20.0);
+}
+
+#[test]
+fn magnitude_query_matches_processed_sine() {
+    let spec = FilterSpec::new(FilterPrototype::Butterworth, 4, 1_500_000.0);
+    let filter = spec.design(FS);
+    for freq in [500_000.0, 1_500_000.0, 3_000_000.0] {
+        let predicted = filter.magnitude(freq);
+        let measured = measured_gain(&spec, freq);
+        assert!(
+            (predicted - measured).abs() < 0.01,
+            "{freq} Hz: {predicted} vs {measured}"
+        );
+    }
+}
+
+#[test]
+fn bessel_group_delay_is_flatter_than_butterworth() {
+    let spread = |prototype| {
+        let filter = FilterSpec::new(prototype, 4, 1_000_000.0).design(FS);
+        let dc = filter.group_delay_s(10_000.0);
+        let passband = filter.group_delay_s(800_000.0);
+        (passband - dc).abs() / dc
+    };
+    assert!(spread(FilterPrototype::Bessel) < 0.5 * spread(FilterPrototype::Butterworth));
+}
+
+#[test]
+fn high_order_bessel_and_gaussian_stay_stable_with_flat_delay() {
+    for prototype in [FilterPrototype::Bessel, FilterPrototype::Gaussian] {
+        let sixth = FilterSpec::new(prototype, 6, 1_000_000.0).design(FS);
+        for order in 7..=MAX_ORDER {
+            let spec = FilterSpec::new(prototype, order, 1_000_000.0);
+            let filter = spec.design(FS);
+            for freq in [500_000.0, 2_000_000.0] {
+                let (predicted, measured) = (filter.magnitude(freq), measured_gain(&spec, freq));
+                assert!(
+                    (predicted - measured).abs() < 0.01,
+                    "{prototype:?} order {order} at {freq} Hz: {predicted} vs {measured}"
+                );
+            }
+            assert!(filter.magnitude_db(3_000_000.0) < sixth.magnitude_db(3_000_000.0));
+            let dc = filter.group_delay_s(10_000.0);
+            let spread = (filter.group_delay_s(800_000.0) - dc).abs() / dc;
+            assert!(
+                dc > 0.0 && spread < 0.05,
+                "{prototype:?} order {order}: group delay spread {spread}"
+            );
+        }
+    }
+}
+
+#[test]
+fn fir_designs_pass_and_reject_the_right_bands() {
+    let lowpass = FirFilter::windowed_sinc_lowpass(2_000_000.0, FS, 0.0);
+    assert!((lowpass.magnitude(100_000.0) - 1.0).abs() < 0.01);
+    assert!(lowpass.magnitude_db(6_000_000.0) < -40.0);
+    assert!(lowpass.group_delay_samples(1_000_000.0).abs() < 1e-6);
+
+    let bandpass = FirFilter::windowed_sinc_bandpass(3_000_000.0, 4_200_000.0, FS);
+    assert!((bandpass.magnitude(3_600_000.0) - 1.0).abs() < 0.05);
+    assert!(bandpass.magnitude_db(500_000.0) < -30.0);
+
+    let notch = FirFilter::windowed_sinc_notch(3_579_545.0, 1_000_000.0, FS);
+    assert!(notch.magnitude_db(3_579_545.0) < -30.0);
+    assert!((notch.magnitude(0.0) - 1.0).abs() < 0.01);
+}
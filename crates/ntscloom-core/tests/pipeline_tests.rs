@@ -1,5 +1,14 @@
+use std::ops::Range;
+
 use approx::assert_relative_eq;
-use ntscloom_core::{process_frame, rgb_to_yiq, yiq_to_rgb, Frame, PipelineConfig};
+use ntscloom_core::dsp::{linear_to_srgb, srgb_to_linear};
+use ntscloom_core::pipeline::FRAME_RATE_HZ;
+use ntscloom_core::{
+    illegal_mask, process_frame, rgb_to_yiq, yiq_to_rgb, AxisProfile, CameraTransfer,
+    ChromaSubsampling, ClampMode, DemodulationFilter, DisplayTransfer, DropoutCompensation, Frame,
+    LegalizerMode, Pipeline, PipelineConfig, RasterView, SignalDomain, TapeDamageEvent,
+    TapeDamageKind, Yiq,
+};
 
 #[test]
 fn yiq_roundtrip_preserves_luma() {
@@ -60,3 +69,1044 @@ fn uniform_frame_has_low_column_variance() {
         .fold(0.0_f32, f32::max);
     assert!(max_dev < 0.02, "column variance too high: {max_dev}");
 }
+
+fn quiet_config() -> PipelineConfig {
+    let mut config = PipelineConfig::default();
+    config.channel.luma_ringing = 0.0;
+    config.channel.luma_noise = 0.0;
+    config.tape.flutter_depth = 0.0;
+    config.tape.tracking_error = 0.0;
+    config.artifacts.head_switch_enabled = false;
+    config.artifacts.vertical_jitter_enabled = false;
+    config.artifacts.horizontal_tbc_enabled = false;
+    config.artifacts.chroma_phase_drift_enabled = false;
+    config.artifacts.dropout_enabled = false;
+    config.artifacts.saturation_enabled = false;
+    config.precision.pll_phase_noise = 0.0;
+    config
+}
+
+/// Largest absolute difference over every RGB channel of every pixel.
+fn max_channel_deviation(frame: &Frame, reference: &Frame) -> f32 {
+    frame
+        .data
+        .iter()
+        .zip(reference.data.iter())
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0_f32, f32::max)
+}
+
+#[test]
+fn dropout_compensator_hides_dropouts_on_uniform_field() {
+    let mut frame = Frame::new(64, 32);
+    frame.data.fill(0.5);
+
+    let clean = process_frame(&frame, &quiet_config(), 14_318_180.0);
+
+    let mut config = quiet_config();
+    config.artifacts.dropout_enabled = true;
+    config.artifacts.dropout_rate = 0.01;
+    config.tape.dropout_threshold = 1.0;
+    config.tape.dropout_compensation = DropoutCompensation::Doc;
+    let compensated = process_frame(&frame, &config, 14_318_180.0);
+    let doc_dev = max_channel_deviation(&compensated, &clean);
+
+    config.tape.dropout_compensation = DropoutCompensation::WhiteSparkles;
+    let sparkles = process_frame(&frame, &config, 14_318_180.0);
+    let sparkle_dev = max_channel_deviation(&sparkles, &clean);
+
+    assert!(doc_dev < 0.02, "DOC left visible dropouts: {doc_dev}");
+    assert!(sparkle_dev > 0.1, "expected white sparkles without DOC: {sparkle_dev}");
+}
+
+#[test]
+fn dropout_compensator_repeats_the_previous_line() {
+    // Rows alternate between two greys, so a patched run shows the other grey.
+    let mut frame = Frame::new(96, 32);
+    for (n, px) in frame.data.chunks_mut(3).enumerate() {
+        px.fill(if (n / 96) % 2 == 0 { 0.3 } else { 0.6 });
+    }
+
+    let mut config = quiet_config();
+    config.artifacts.crosstalk_dynamic = false;
+    let clean = process_frame(&frame, &config, 14_318_180.0);
+
+    config.artifacts.dropout_enabled = true;
+    config.artifacts.dropout_rate = 0.0005;
+    config.artifacts.dropout_length = 0.5;
+    config.tape.dropout_threshold = 1.0;
+    config.tape.dropout_compensation = DropoutCompensation::Doc;
+    let compensated = process_frame(&frame, &config, 14_318_180.0);
+
+    // Compare four-pixel means: the residual dot pattern repeats every four
+    // pixels and flips phase from line to line, but its mean does not.
+    let mut patched = 0;
+    for y in 1..32 {
+        let previous = luma_row(&clean, y - 1);
+        let current = luma_row(&clean, y);
+        let out = luma_row(&compensated, y);
+        for x in (8..88).step_by(4) {
+            if !(x..x + 4).all(|i| (out[i] - current[i]).abs() > 0.2) {
+                continue;
+            }
+            let mean = |row: &[f32]| row[x..x + 4].iter().sum::<f32>() / 4.0;
+            patched += 1;
+            assert_relative_eq!(mean(&out), mean(&previous), epsilon = 0.02);
+        }
+    }
+    assert!(patched > 0, "expected some dropouts to be patched");
+}
+
+fn row_deviation(frame: &Frame, reference: &Frame, y: usize) -> f32 {
+    let start = y * frame.width * 3;
+    let end = start + frame.width * 3;
+    frame.data[start..end]
+        .iter()
+        .zip(reference.data[start..end].iter())
+        .map(|(a, b)| (a - b).abs())
+        .fold(0.0_f32, f32::max)
+}
+
+#[test]
+fn crease_event_damages_its_band_only_while_active() {
+    let mut frame = Frame::new(64, 64);
+    frame.data.fill(0.5);
+    let mut clean_pipeline = Pipeline::new(quiet_config(), 14_318_180.0);
+    let clean = clean_pipeline.process(&frame);
+    let clean_after = clean_pipeline.process(&frame);
+
+    let mut config = quiet_config();
+    config.tape.dropout_compensation = DropoutCompensation::WhiteSparkles;
+    config.tape.damage_events.push(TapeDamageEvent {
+        kind: TapeDamageKind::Crease,
+        start_s: 0.0,
+        duration_s: 0.02,
+        severity: 1.0,
+        position: 0.5,
+    });
+    let mut pipeline = Pipeline::new(config, 14_318_180.0);
+    let damaged = pipeline.process(&frame);
+    let after = pipeline.process(&frame);
+    assert_eq!(pipeline.frame_index(), 2);
+
+    let band = (28..36).map(|y| row_deviation(&damaged, &clean, y)).fold(0.0_f32, f32::max);
+    let top = (0..16).map(|y| row_deviation(&damaged, &clean, y)).fold(0.0_f32, f32::max);
+    assert!(band > 0.1, "crease band not damaged: {band}");
+    assert!(top < 0.02, "damage outside crease band: {top}");
+    assert!(max_channel_deviation(&after, &clean_after) < 0.02, "crease persisted past its duration");
+}
+
+#[test]
+fn wrinkle_event_sweeps_down_the_picture_over_several_frames() {
+    let mut frame = Frame::new(64, 64);
+    frame.data.fill(0.5);
+    let mut clean_pipeline = Pipeline::new(quiet_config(), 14_318_180.0);
+    let clean: Vec<Frame> = (0..5).map(|_| clean_pipeline.process(&frame)).collect();
+
+    // Active from the start of frame 1 to the end of frame 3.
+    let mut config = quiet_config();
+    config.tape.damage_events.push(TapeDamageEvent {
+        kind: TapeDamageKind::Wrinkle,
+        start_s: 1.0 / FRAME_RATE_HZ,
+        duration_s: 3.0 / FRAME_RATE_HZ,
+        severity: 1.0,
+        position: 0.0,
+    });
+    let mut pipeline = Pipeline::new(config, 14_318_180.0);
+    let damaged: Vec<Frame> = (0..5).map(|_| pipeline.process(&frame)).collect();
+
+    let band = |index: usize, rows: Range<usize>| {
+        rows.map(|y| row_deviation(&damaged[index], &clean[index], y)).fold(0.0_f32, f32::max)
+    };
+    assert!(max_channel_deviation(&damaged[0], &clean[0]) < 0.02, "damaged before the event");
+    assert!(band(1, 0..8) > 0.1, "first frame: wrinkle not at the top");
+    assert!(band(1, 24..40) < 0.02, "first frame: wrinkle already in the middle");
+    assert!(band(2, 28..36) > 0.1, "middle frame: wrinkle not in the middle");
+    assert!(band(2, 0..8).max(band(2, 56..64)) < 0.02, "middle frame: wrinkle too tall");
+    assert!(band(3, 56..64) > 0.1, "last frame: wrinkle not at the bottom");
+    assert!(band(3, 24..40) < 0.02, "last frame: wrinkle left behind in the middle");
+    assert!(max_channel_deviation(&damaged[4], &clean[4]) < 0.02, "damaged after the event");
+}
+
+fn red_blue_edge_frame() -> Frame {
+    let mut frame = Frame::new(64, 16);
+    for y in 0..frame.height {
+        for x in 0..frame.width {
+            let idx = (y * frame.width + x) * 3;
+            let color = if x < 32 { [1.0, 0.1, 0.1] } else { [0.1, 0.1, 1.0] };
+            frame.data[idx..idx + 3].copy_from_slice(&color);
+        }
+    }
+    frame
+}
+
+#[test]
+fn dot_crawl_repeats_on_a_two_frame_cycle() {
+    let frame = red_blue_edge_frame();
+
+    let mut config = quiet_config();
+    config.channel.dot_crawl_intensity = 1.0;
+    let mut pipeline = Pipeline::new(config, 14_318_180.0);
+    let first = pipeline.process(&frame);
+    let second = pipeline.process(&frame);
+    let third = pipeline.process(&frame);
+
+    assert!(max_channel_deviation(&first, &second) > 0.01, "dot pattern did not move between frames");
+    assert!(max_channel_deviation(&first, &third) < 1e-4, "dot pattern should repeat every two frames");
+}
+
+#[test]
+fn dot_crawl_intensity_scales_the_crosstalk_leakage() {
+    let frame = red_blue_edge_frame();
+    let render = |intensity: f32, crosstalk: bool| {
+        let mut config = quiet_config();
+        config.channel.dot_crawl_intensity = intensity;
+        config.artifacts.crosstalk_dynamic = crosstalk;
+        process_frame(&frame, &config, 14_318_180.0)
+    };
+    let none = render(0.0, true);
+    let full = render(1.0, true);
+    assert!(max_channel_deviation(&none, &render(1.0, false)) < 1e-6, "intensity 0 still leaks");
+    assert!(max_channel_deviation(&full, &none) > 0.01, "intensity 1 adds no crosstalk");
+}
+
+fn luma_row(frame: &Frame, y: usize) -> Vec<f32> {
+    (0..frame.width)
+        .map(|x| {
+            let idx = (y * frame.width + x) * 3;
+            (frame.data[idx] + frame.data[idx + 1] + frame.data[idx + 2]) / 3.0
+        })
+        .collect()
+}
+
+#[test]
+fn peaking_and_brick_wall_limits_ring_around_edges() {
+    let mut frame = Frame::new(96, 4);
+    for y in 0..frame.height {
+        for x in 0..frame.width {
+            let idx = (y * frame.width + x) * 3;
+            let level = if x < 48 { 0.3 } else { 0.6 };
+            frame.data[idx..idx + 3].fill(level);
+        }
+    }
+    // Plateau means start at column 10, clear of the decoder filters settling at the line start.
+    let mean = |row: &[f32]| row.iter().sum::<f32>() / row.len() as f32;
+
+    // The reference is the smooth (Hann) band limit, which itself barely rings.
+    let mut config = quiet_config();
+    config.artifacts.crosstalk_dynamic = false;
+    // Linear-light encoding keeps the step (and the ringing bounds below) as originally tuned.
+    config.composite.signal_domain = SignalDomain::Linear;
+    let flat = luma_row(&process_frame(&frame, &config, 14_318_180.0), 2);
+    let flat_overshoot = flat[48..60].iter().cloned().fold(0.0_f32, f32::max) - mean(&flat[70..]);
+    let flat_undershoot = mean(&flat[10..30]) - flat[36..48].iter().cloned().fold(1.0_f32, f32::min);
+
+    config.channel.luma_peaking = 1.0;
+    let peaked = luma_row(&process_frame(&frame, &config, 14_318_180.0), 2);
+    let overshoot = peaked[48..60].iter().cloned().fold(0.0_f32, f32::max) - mean(&peaked[70..]);
+    let undershoot = mean(&peaked[10..30]) - peaked[36..48].iter().cloned().fold(1.0_f32, f32::min);
+
+    assert!(overshoot > flat_overshoot + 0.01, "peaking produced no overshoot: {overshoot}");
+    assert!(undershoot > flat_undershoot + 0.02, "peaking produced no undershoot: {undershoot}");
+
+    config.channel.luma_peaking = 0.0;
+    config.channel.luma_ringing = 1.0;
+    let ringing = luma_row(&process_frame(&frame, &config, 14_318_180.0), 2);
+    let gibbs = mean(&ringing[10..30]) - ringing[36..48].iter().cloned().fold(1.0_f32, f32::min);
+    // The brick-wall's first lobe is ~9% of the step; after the decoder's luma lowpass it
+    // still undershoots about 0.008 below the Hann reference.
+    assert!(gibbs > flat_undershoot + 0.005, "brick-wall band limit did not ring: {gibbs}");
+
+    // Without ringing the band limit stays in place; only the overshoot goes.
+    let mut frame = Frame::new(96, 4);
+    for (x, pixel) in frame.data.chunks_mut(3).enumerate() {
+        pixel.fill(if (x / 2) % 2 == 0 { 0.2 } else { 0.7 });
+    }
+    config.channel.luma_bandwidth_mhz = 1.0;
+    let swing = |ringing: f32, config: &mut PipelineConfig| {
+        config.channel.luma_ringing = ringing;
+        let row = luma_row(&process_frame(&frame, config, 14_318_180.0), 2);
+        let (low, high) = row[20..76].iter().fold((1.0_f32, 0.0_f32), |(low, high), &v| {
+            (low.min(v), high.max(v))
+        });
+        high - low
+    };
+    let smooth = swing(0.0, &mut config);
+    let slight = swing(0.05, &mut config);
+    assert!((smooth - slight).abs() < 0.02, "band limit dropped out: {smooth} vs {slight}");
+}
+
+#[test]
+fn chroma_noise_adds_colour_confetti_to_grey() {
+    let mut frame = Frame::new(64, 16);
+    frame.data.fill(0.5);
+    let chroma_spread = |out: &Frame| {
+        let sum: f32 = out.data.chunks(3).map(|px| (px[0] - px[2]).abs()).sum();
+        sum / (out.width * out.height) as f32
+    };
+
+    let mut config = quiet_config();
+    let clean = process_frame(&frame, &config, 14_318_180.0);
+    config.channel.chroma_noise = 0.2;
+    let noisy = process_frame(&frame, &config, 14_318_180.0);
+
+    let clean_spread = chroma_spread(&clean);
+    let noisy_spread = chroma_spread(&noisy);
+    assert!(
+        noisy_spread > clean_spread + 0.03,
+        "chroma noise produced no confetti: {clean_spread} -> {noisy_spread}"
+    );
+}
+
+#[test]
+fn adaptive_comb_follows_vertical_colour_edges() {
+    let mut frame = Frame::new(64, 16);
+    for y in 0..frame.height {
+        let colour = if y < 8 { [0.8, 0.2, 0.2] } else { [0.2, 0.2, 0.8] };
+        for x in 0..frame.width {
+            let idx = (y * frame.width + x) * 3;
+            frame.data[idx..idx + 3].copy_from_slice(&colour);
+        }
+    }
+    let row_difference = |out: &Frame, a: usize, b: usize| {
+        let sum: f32 = (16..48)
+            .map(|x| {
+                let (i, j) = ((a * out.width + x) * 3, (b * out.width + x) * 3);
+                (0..3).map(|c| (out.data[i + c] - out.data[j + c]).abs()).sum::<f32>()
+            })
+            .sum();
+        sum / 32.0
+    };
+    let dot_residue = |out: &Frame| {
+        let row = luma_row(out, 4);
+        let mean = row[16..48].iter().sum::<f32>() / 32.0;
+        row[16..48].iter().map(|v| (v - mean).abs()).fold(0.0_f32, f32::max)
+    };
+    let render = |filter: DemodulationFilter| {
+        let mut config = quiet_config();
+        config.demodulation.filter = filter;
+        process_frame(&frame, &config, 14_318_180.0)
+    };
+
+    let lowpass = render(DemodulationFilter::Lowpass);
+    let comb = render(DemodulationFilter::Comb1D);
+    let adaptive = render(DemodulationFilter::AdaptiveComb);
+    // First blue row against the blue interior: the fixed comb drags red down a line.
+    let comb_smear = row_difference(&comb, 8, 12);
+    let adaptive_smear = row_difference(&adaptive, 8, 12);
+    assert!(adaptive_smear < 0.3 * comb_smear, "adaptive comb smeared: {adaptive_smear} vs {comb_smear}");
+    assert!(dot_residue(&adaptive) < 0.25 * dot_residue(&lowpass), "adaptive comb left dot crawl in flat colour");
+
+    let mut config = quiet_config();
+    config.demodulation.filter = DemodulationFilter::AdaptiveComb;
+    config.debug.diagnostic_mode = true;
+    config.debug.show_comb_blend = true;
+    let blend = process_frame(&frame, &config, 14_318_180.0);
+    let pixel = |y: usize| {
+        let idx = (y * blend.width + 32) * 3;
+        (blend.data[idx], blend.data[idx + 1], blend.data[idx + 2])
+    };
+    let (up, fallback, down) = pixel(3);
+    assert!(fallback < 0.1 && (up - down).abs() < 0.1, "interior should comb both ways");
+    assert!(pixel(7).0 > 0.9, "last red row should comb with the line above");
+    assert!(pixel(8).2 > 0.9, "first blue row should comb with the line below");
+}
+
+#[test]
+fn comb_3d_removes_frame_flicker_on_stills_and_falls_back_on_motion() {
+    let pattern = |offset: usize| {
+        let mut frame = Frame::new(64, 16);
+        for y in 0..frame.height {
+            let tint = if (y + offset).is_multiple_of(2) { [0.7, 0.3, 0.3] } else { [0.3, 0.3, 0.7] };
+            for x in 0..frame.width {
+                let idx = (y * frame.width + x) * 3;
+                let detail = if (x / 2).is_multiple_of(2) { -0.15 } else { 0.15 };
+                for (value, level) in frame.data[idx..idx + 3].iter_mut().zip(tint) {
+                    *value = level + detail;
+                }
+            }
+        }
+        frame
+    };
+    let mean_difference = |a: &Frame, b: &Frame| {
+        a.data.iter().zip(&b.data).map(|(p, q)| (p - q).abs()).sum::<f32>() / a.data.len() as f32
+    };
+    let flicker = |filter: DemodulationFilter| {
+        let mut config = quiet_config();
+        config.demodulation.filter = filter;
+        let mut pipeline = Pipeline::new(config, 14_318_180.0);
+        pipeline.process(&pattern(0));
+        pipeline.process(&pattern(0));
+        let a = pipeline.process(&pattern(0));
+        let b = pipeline.process(&pattern(0));
+        mean_difference(&a, &b)
+    };
+    // Rows alternate colour, so the 2D comb falls back to notch separation and the luma
+    // detail near the subcarrier flickers as cross-colour from frame to frame.
+    let adaptive = flicker(DemodulationFilter::AdaptiveComb);
+    let comb_3d = flicker(DemodulationFilter::Comb3D);
+    assert!(comb_3d < 0.25 * adaptive, "3D comb still flickers: {comb_3d} vs {adaptive}");
+
+    let mut config = quiet_config();
+    config.demodulation.filter = DemodulationFilter::Comb3D;
+    let mut pipeline = Pipeline::new(config.clone(), 14_318_180.0);
+    pipeline.process(&pattern(0));
+    pipeline.process(&pattern(0));
+    let moving = pipeline.process(&pattern(1));
+    config.demodulation.filter = DemodulationFilter::AdaptiveComb;
+    let mut reference = Pipeline::new(config, 14_318_180.0);
+    reference.seek(2);
+    let spatial = reference.process(&pattern(1));
+    assert!(mean_difference(&moving, &spatial) < 0.01, "3D comb ghosted the previous frame");
+}
+
+#[test]
+fn decoder_axis_profiles_derive_their_matrix() {
+    for profile in [AxisProfile::TextbookIq, AxisProfile::EquibandColorDifference] {
+        let transfer = profile.axes().transfer();
+        for (row, expected) in transfer.iter().zip([[1.0, 0.0], [0.0, 1.0]]) {
+            for (value, expected) in row.iter().zip(expected) {
+                assert!((value - expected).abs() < 1e-4, "{profile:?} is not transparent: {transfer:?}");
+            }
+        }
+    }
+
+    let transfer = AxisProfile::ConsumerRedPush.axes().transfer();
+    let push = |yiq: Yiq| Yiq {
+        y: yiq.y,
+        i: transfer[0][0] * yiq.i + transfer[0][1] * yiq.q,
+        q: transfer[1][0] * yiq.i + transfer[1][1] * yiq.q,
+    };
+    let (r, g, _) = yiq_to_rgb(push(rgb_to_yiq(0.8, 0.15, 0.15)));
+    assert!(r - g > 0.65 + 0.05, "red push should oversaturate red: {r} {g}");
+    let hue = |yiq: Yiq| yiq.q.atan2(yiq.i).to_degrees();
+    let flesh = rgb_to_yiq(0.9, 0.6, 0.45);
+    assert!(hue(push(flesh)) > hue(flesh) + 5.0, "flesh tones should turn towards red");
+
+    let mut frame = Frame::new(32, 8);
+    for px in frame.data.chunks_mut(3) {
+        px.copy_from_slice(&[0.8, 0.15, 0.15]);
+    }
+    let render = |profile: AxisProfile| {
+        let mut config = quiet_config();
+        config.demodulation.axes = profile.axes();
+        let out = process_frame(&frame, &config, 14_318_180.0);
+        let idx = (4 * out.width + 16) * 3;
+        (out.data[idx], out.data[idx + 1])
+    };
+    let (textbook_r, textbook_g) = render(AxisProfile::TextbookIq);
+    let (equiband_r, equiband_g) = render(AxisProfile::EquibandColorDifference);
+    let (push_r, push_g) = render(AxisProfile::ConsumerRedPush);
+    assert!((textbook_r - equiband_r).abs() < 0.03 && (textbook_g - equiband_g).abs() < 0.03);
+    assert!(push_r - push_g > textbook_r - textbook_g + 0.03, "red push decoder did not push red");
+}
+
+#[test]
+fn receiver_picture_controls_work_on_the_decoded_signal() {
+    let patch = |rgb: [f32; 3]| {
+        let mut frame = Frame::new(32, 8);
+        for px in frame.data.chunks_mut(3) {
+            px.copy_from_slice(&rgb);
+        }
+        frame
+    };
+    let centre = |frame: &Frame, config: &PipelineConfig| {
+        let out = process_frame(frame, config, 14_318_180.0);
+        let idx = (4 * out.width + 16) * 3;
+        [out.data[idx], out.data[idx + 1], out.data[idx + 2]]
+    };
+
+    let mut config = quiet_config();
+    config.receiver.color = 0.0;
+    let [r, g, b] = centre(&patch([0.8, 0.15, 0.15]), &config);
+    assert!((r - g).abs() < 0.01 && (g - b).abs() < 0.01, "colour 0 should give greyscale");
+
+    let flesh = patch([0.9, 0.6, 0.45]);
+    let neutral = centre(&flesh, &quiet_config());
+    let mut config = quiet_config();
+    config.receiver.tint_deg = 30.0;
+    let tinted = centre(&flesh, &config);
+    assert!(tinted[1] - tinted[0] > neutral[1] - neutral[0] + 0.05, "positive tint should turn flesh green");
+
+    let mut config = quiet_config();
+    config.receiver.contrast = 6.0;
+    config.receiver.brightness = -0.5;
+    config.receiver.color = 0.0;
+    let grey = centre(&patch([0.5, 0.5, 0.5]), &config);
+    let light = centre(&patch([0.8, 0.8, 0.8]), &config);
+    assert!((grey[1] - light[1]).abs() < 0.01, "overdriven contrast should clip whites together");
+    assert!(light[1] < 0.95, "the clip is in the video amplifier, before the brightness level");
+
+    let mut config = quiet_config();
+    config.receiver.brightness = 0.2;
+    // In linear light the lift is not crushed by the display transfer function.
+    config.composite.signal_domain = SignalDomain::Linear;
+    let black = centre(&patch([0.0, 0.0, 0.0]), &config);
+    assert!(black[1] > 0.3, "brightness should lift black: {black:?}");
+
+    let mut frame = Frame::new(96, 4);
+    for (n, px) in frame.data.chunks_mut(3).enumerate() {
+        px.fill(if n % 96 < 48 { 0.3 } else { 0.6 });
+    }
+    let overshoot = |sharpness: f32| {
+        let mut config = quiet_config();
+        config.receiver.sharpness = sharpness;
+        let row = luma_row(&process_frame(&frame, &config, 14_318_180.0), 2);
+        row[48..60].iter().cloned().fold(0.0_f32, f32::max) - row[80]
+    };
+    assert!(overshoot(1.5) > overshoot(0.0) + 0.02, "sharpness should overshoot edges");
+}
+
+#[test]
+fn agc_loop_restores_sync_level_and_pumps_when_fast() {
+    let mut frame = Frame::new(256, 64);
+    frame.data.fill(0.5);
+    let row_mean = |frame: &Frame, y: usize| {
+        let row = luma_row(frame, y);
+        row.iter().sum::<f32>() / row.len() as f32
+    };
+    let clean = row_mean(&process_frame(&frame, &quiet_config(), 14_318_180.0), 32);
+
+    let mut config = quiet_config();
+    config.tape.tracking_error = 0.4;
+    config.receiver.clamp = ClampMode::BackPorch;
+    config.receiver.agc_enabled = true;
+    config.receiver.agc_time_constant_ms = 1.0;
+    let out = process_frame(&frame, &config, 14_318_180.0);
+    assert!(row_mean(&out, 0) < clean - 0.1, "the loop should start from unity gain");
+    assert!((row_mean(&out, 63) - clean).abs() < 0.02, "AGC should settle on the nominal sync level");
+
+    // Same seed with and without the loop, so the spread of the difference is the gain
+    // modulation alone. The faster attack settles a noisy loop slightly below unity, which
+    // only adds a constant offset.
+    let second_frame = |agc_time_constant_ms: Option<f32>| {
+        let mut config = quiet_config();
+        config.channel.luma_noise = 0.05;
+        if let Some(time_constant_ms) = agc_time_constant_ms {
+            config.receiver.agc_enabled = true;
+            config.receiver.agc_time_constant_ms = time_constant_ms;
+        }
+        let mut pipeline = Pipeline::new(config, 14_318_180.0);
+        pipeline.process(&frame);
+        pipeline.process(&frame)
+    };
+    let open_loop = second_frame(None);
+    let pumping = |time_constant_ms: f32| {
+        let out = second_frame(Some(time_constant_ms));
+        let (low, high) = (0..out.height)
+            .map(|y| row_mean(&out, y) - row_mean(&open_loop, y))
+            .fold((f32::MAX, f32::MIN), |(low, high), d| (low.min(d), high.max(d)));
+        high - low
+    };
+    let (fast, slow) = (pumping(0.1), pumping(20.0));
+    assert!(fast > 3.0 * slow && fast > 0.01, "a fast loop should follow sync noise: {fast} vs {slow}");
+}
+
+#[test]
+fn agc_cuts_gain_faster_than_it_raises_it() {
+    let mut frame = Frame::new(128, 160);
+    frame.data.fill(0.5);
+    let row_mean = |frame: &Frame, y: usize| {
+        let row = luma_row(frame, y);
+        row.iter().sum::<f32>() / row.len() as f32
+    };
+    let clean = row_mean(&process_frame(&frame, &quiet_config(), 14_318_180.0), 32);
+    // Fraction of the move from unity gain towards the last line still to go at line 8.
+    let remaining = |agc_reference_ire: f32| {
+        let mut config = quiet_config();
+        config.receiver.agc_enabled = true;
+        config.receiver.agc_time_constant_ms = 5.0;
+        config.receiver.agc_reference_ire = agc_reference_ire;
+        let out = process_frame(&frame, &config, 14_318_180.0);
+        let last = row_mean(&out, 159);
+        (row_mean(&out, 8) - last) / (clean - last)
+    };
+    // A sync twice the reference is cut within a few lines; half the reference is still
+    // being raised a whole time constant later.
+    let (attack, release) = (remaining(20.0), remaining(80.0));
+    assert!(attack < 0.1, "gain reduction should be fast: {attack}");
+    assert!(release > 0.66, "gain increase should follow the time constant: {release}");
+}
+
+#[test]
+fn copy_protection_pulses_pump_the_agc_and_colorstripe_bands_hue() {
+    let mut frame = Frame::new(64, 48);
+    for px in frame.data.chunks_mut(3) {
+        px.copy_from_slice(&[0.7, 0.4, 0.3]);
+    }
+    let mut agc = quiet_config();
+    agc.receiver.agc_enabled = true;
+    let row_mean = |frame: &Frame, y: usize| {
+        let row = luma_row(frame, y);
+        row.iter().sum::<f32>() / row.len() as f32
+    };
+    let clean = process_frame(&frame, &agc, 14_318_180.0);
+
+    let mut config = agc.clone();
+    config.copy_protection.agc_pulses_enabled = true;
+    let protected = process_frame(&frame, &config, 14_318_180.0);
+    assert!(row_mean(&protected, 0) < row_mean(&clean, 0) - 0.05, "AGC pulses should darken the picture");
+    assert!(
+        row_mean(&protected, 47) > row_mean(&protected, 0) + 0.02,
+        "the loop should recover down the picture"
+    );
+
+    // Half an amplitude cycle later the pulses are at blanking level.
+    let mut pipeline = Pipeline::new(config.clone(), 14_318_180.0);
+    pipeline.seek(30);
+    let trough = pipeline.process(&frame);
+    assert!((row_mean(&trough, 0) - row_mean(&clean, 0)).abs() < 0.02, "darkening should cycle");
+
+    config.receiver.agc_enabled = false;
+    let no_agc = process_frame(&frame, &config, 14_318_180.0);
+    assert!(max_channel_deviation(&no_agc, &process_frame(&frame, &quiet_config(), 14_318_180.0)) < 1e-3);
+
+    let mut config = quiet_config();
+    config.copy_protection.colorstripe_enabled = true;
+    let striped = process_frame(&frame, &config, 14_318_180.0);
+    assert!(row_deviation(&striped, &clean, 1) > 0.05, "striped lines should shift hue");
+    assert!(row_deviation(&striped, &clean, 12) < 0.02, "the burst lock should recover between stripes");
+    assert!(row_deviation(&striped, &clean, 18) > 0.05, "the stripe pattern should repeat");
+}
+
+/// Chroma (amplitude, phase in degrees) at the centre of each step of a modulated staircase.
+/// The first step is repeated as a lead-in so the line-start transient has settled.
+fn modulated_staircase(config: &PipelineConfig, steps: &[f32]) -> Vec<(f32, f32)> {
+    // The staircase is built and measured in linear light.
+    let mut config = config.clone();
+    config.composite.signal_domain = SignalDomain::Linear;
+    let step_width = 32;
+    let mut frame = Frame::new((steps.len() + 1) * step_width, 8);
+    for (n, px) in frame.data.chunks_mut(3).enumerate() {
+        let step = ((n % frame.width) / step_width).saturating_sub(1);
+        let (r, g, b) = yiq_to_rgb(Yiq { y: steps[step], i: 0.08, q: 0.0 });
+        px.copy_from_slice(&[linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b)]);
+    }
+    let out = process_frame(&frame, &config, 14_318_180.0);
+    (0..steps.len())
+        .map(|k| {
+            let (mut i, mut q) = (0.0, 0.0);
+            for x in (k + 1) * step_width + 12..(k + 1) * step_width + 20 {
+                let idx = (4 * out.width + x) * 3;
+                let linear = |v: f32| srgb_to_linear(v);
+                let yiq = rgb_to_yiq(linear(out.data[idx]), linear(out.data[idx + 1]), linear(out.data[idx + 2]));
+                i += yiq.i / 8.0;
+                q += yiq.q / 8.0;
+            }
+            (i.hypot(q), q.atan2(i).to_degrees())
+        })
+        .collect()
+}
+
+#[test]
+fn differential_gain_and_phase_show_on_a_modulated_staircase() {
+    let steps = [0.15, 0.29, 0.43, 0.57, 0.71, 0.85];
+    let dg_dp = |chroma: &[(f32, f32)]| {
+        let (first, last) = (chroma[0], chroma[chroma.len() - 1]);
+        (100.0 * (last.0 / first.0 - 1.0), last.1 - first.1)
+    };
+
+    // A line comb keeps luma out of the demodulators, so the staircase itself reads flat.
+    let mut config = quiet_config();
+    config.demodulation.filter = DemodulationFilter::AdaptiveComb;
+    let (dg, dp) = dg_dp(&modulated_staircase(&config, &steps));
+    assert!(dg.abs() < 1.0 && dp.abs() < 1.0, "clean channel should be flat: {dg}% {dp}°");
+
+    config.channel.differential_gain_pct_per_ire = 0.1;
+    config.channel.differential_phase_deg_per_ire = 0.1;
+    let linear = modulated_staircase(&config, &steps);
+    let (dg, dp) = dg_dp(&linear);
+    assert!((5.0..9.0).contains(&dg), "expected ~7% DG over 15..85 IRE: {dg}");
+    assert!((5.0..9.0).contains(&dp.abs()), "expected ~7° DP over 15..85 IRE: {dp}");
+
+    config.channel.differential_curve_exponent = 2.0;
+    let curved = modulated_staircase(&config, &steps);
+    let mid_shift = |chroma: &[(f32, f32)]| (chroma[2].1 - chroma[0].1).abs();
+    assert!(mid_shift(&curved) < 0.7 * mid_shift(&linear), "exponent should push DP to the highlights");
+}
+
+#[test]
+fn yc_delay_and_chroma_group_delay_offset_colour_from_edges() {
+    let mut frame = Frame::new(128, 8);
+    for (n, px) in frame.data.chunks_mut(3).enumerate() {
+        let rgb = if n % 128 < 64 { [0.45, 0.45, 0.45] } else { [0.8, 0.3, 0.3] };
+        px.copy_from_slice(&rgb);
+    }
+    let decoded_row = |config: &PipelineConfig| {
+        let out = process_frame(&frame, config, 14_318_180.0);
+        (0..out.width)
+            .map(|x| {
+                let idx = (4 * out.width + x) * 3;
+                let linear = |v: f32| srgb_to_linear(v);
+                rgb_to_yiq(linear(out.data[idx]), linear(out.data[idx + 1]), linear(out.data[idx + 2]))
+            })
+            .collect::<Vec<Yiq>>()
+    };
+    // Fractional position where a component is halfway between its two plateaus.
+    let edge = |values: Vec<f32>| {
+        let left = values[20..40].iter().sum::<f32>() / 20.0;
+        let right = values[100..120].iter().sum::<f32>() / 20.0;
+        let half = 0.5 * (left + right);
+        let x = (40..120).find(|&x| (values[x] - half) * (right - left) > 0.0).unwrap();
+        x as f32 - (values[x] - half) / (values[x] - values[x - 1])
+    };
+    let edges = |config: &PipelineConfig| {
+        let row = decoded_row(config);
+        let plateau = row[104..120].iter().fold((0.0, 0.0), |(i, q), p| (i + p.i / 16.0, q + p.q / 16.0));
+        (edge(row.iter().map(|p| p.y).collect()), edge(row.iter().map(|p| p.i).collect()), plateau)
+    };
+
+    // The decoder runs at pixel rate whatever the oversampling, so its luma lowpass lets
+    // the same subcarrier residue through on the red plateau.
+    let residue = |config: &PipelineConfig| {
+        let row = decoded_row(config);
+        let luma = row[100..120].iter().map(|p| p.y);
+        luma.clone().fold(f32::MIN, f32::max) - luma.fold(f32::MAX, f32::min)
+    };
+    let mut config = quiet_config();
+    config.precision.oversample_factor = 1;
+    let residue_1x = residue(&config);
+    config.precision.oversample_factor = 2;
+    let residue_2x = residue(&config);
+    assert!(
+        (residue_2x / residue_1x - 1.0).abs() < 0.25,
+        "luma lowpass cutoff depends on oversampling: {residue_1x} vs {residue_2x}"
+    );
+
+    let (luma_edge, chroma_edge, plateau) = edges(&config);
+    assert!(chroma_edge > luma_edge + 0.5, "chroma filters should delay colour: {luma_edge} {chroma_edge}");
+
+    config.channel.yc_delay_ns = 300.0;
+    let (_, delayed_edge, delayed_plateau) = edges(&config);
+    let shift_px = delayed_edge - chroma_edge;
+    assert!((3.5..5.0).contains(&shift_px), "300 ns is ~4.3 px at 4fsc: {shift_px}");
+    assert!(
+        (delayed_plateau.0 - plateau.0).abs() < 0.01 && (delayed_plateau.1 - plateau.1).abs() < 0.01,
+        "burst should carry the delay's phase shift: {plateau:?} {delayed_plateau:?}"
+    );
+
+    config.channel.yc_delay_ns = -300.0;
+    let (_, advanced_edge, _) = edges(&config);
+    assert!(advanced_edge < chroma_edge - 3.5, "negative delay should lead luma: {advanced_edge}");
+}
+
+#[test]
+fn ac_coupling_tilts_lines_and_fields_and_bounces_on_scene_changes() {
+    let mut grey = Frame::new(128, 96);
+    grey.data.fill(0.4);
+    let mut boxed = Frame::new(128, 96);
+    boxed.data.fill(0.4);
+    for y in 16..40 {
+        boxed.data[(y * 128 + 8) * 3..(y * 128 + 56) * 3].fill(1.0);
+    }
+    let mean = |frame: &Frame, y: usize, columns: Range<usize>| {
+        let n = columns.len() as f32;
+        luma_row(frame, y)[columns].iter().sum::<f32>() / n
+    };
+
+    // Line coupling: a streak right of the box that the porch clamp cannot remove.
+    let mut config = quiet_config();
+    config.channel.line_coupling_us = 30.0;
+    config.receiver.clamp = ClampMode::BackPorch;
+    let out = process_frame(&boxed, &config, 14_318_180.0);
+    let streak = mean(&out, 8, 64..80) - mean(&out, 28, 64..80);
+    assert!(streak > 0.05, "bright box should drag the rest of its lines down: {streak}");
+
+    // Field coupling: a shadow below the box, restored by the clamp.
+    let shadow = |clamp: ClampMode| {
+        let mut config = quiet_config();
+        config.channel.field_coupling_ms = 5.0;
+        config.receiver.clamp = clamp;
+        // In linear light the shadow is not crushed by the display transfer function.
+        config.composite.signal_domain = SignalDomain::Linear;
+        let reference = process_frame(&grey, &config, 14_318_180.0);
+        mean(&reference, 70, 8..56) - mean(&process_frame(&boxed, &config, 14_318_180.0), 70, 8..56)
+    };
+    let (unclamped, clamped) = (shadow(ClampMode::Off), shadow(ClampMode::BackPorch));
+    assert!(unclamped > 0.01 && clamped.abs() < 0.002, "field tilt: {unclamped} clamped {clamped}");
+
+    // Bounce: a cut from black to grey overshoots and settles over the following frames.
+    let mut config = quiet_config();
+    config.channel.field_coupling_ms = 5.0;
+    let mut pipeline = Pipeline::new(config, 14_318_180.0);
+    for _ in 0..10 {
+        pipeline.process(&Frame::new(128, 96));
+    }
+    let first = mean(&pipeline.process(&grey), 2, 0..128);
+    for _ in 0..4 {
+        pipeline.process(&grey);
+    }
+    let settled = mean(&pipeline.process(&grey), 2, 0..128);
+    assert!(first > settled + 0.03, "scene change should overshoot: {first} -> {settled}");
+}
+
+#[test]
+fn signal_domain_and_transfer_functions_shape_tones_and_noise() {
+    let mut frame = Frame::new(64, 32);
+    for (n, px) in frame.data.chunks_mut(3).enumerate() {
+        px.fill(if n % 64 < 32 { 0.1 } else { 0.8 });
+    }
+    // Mean and spread of output luma over the inside of each half.
+    let patch = |config: &PipelineConfig, columns: Range<usize>| {
+        let out = process_frame(&frame, config, 14_318_180.0);
+        let values: Vec<f32> = (4..28).flat_map(|y| luma_row(&out, y)[columns.clone()].to_vec()).collect();
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32;
+        (mean, variance.sqrt())
+    };
+    // The line comb keeps the setup pedestal out of the chroma demodulators.
+    let config_for = |domain: SignalDomain, camera: CameraTransfer, display: DisplayTransfer| {
+        let mut config = quiet_config();
+        config.demodulation.filter = DemodulationFilter::AdaptiveComb;
+        config.composite.signal_domain = domain;
+        config.composite.camera_transfer = camera;
+        config.receiver.display_transfer = display;
+        config
+    };
+
+    let srgb = config_for(SignalDomain::GammaEncoded, CameraTransfer::Srgb, DisplayTransfer::Srgb);
+    let linear = config_for(SignalDomain::Linear, CameraTransfer::Bt601, DisplayTransfer::Crt22);
+    for config in [&srgb, &linear] {
+        let (dark, bright) = (patch(config, 8..24).0, patch(config, 40..56).0);
+        assert!((dark - 0.1).abs() < 0.01 && (bright - 0.8).abs() < 0.01, "tones should survive: {dark} {bright}");
+    }
+
+    // BT.601's linear toe into a CRT power law crushes shadows; gamma 2.5 darkens further.
+    let crt22 = config_for(SignalDomain::GammaEncoded, CameraTransfer::Bt601, DisplayTransfer::Crt22);
+    let crt25 = config_for(SignalDomain::GammaEncoded, CameraTransfer::Bt601, DisplayTransfer::Crt25);
+    assert!(patch(&crt22, 8..24).0 < 0.05, "BT.601 into a CRT should crush the shadows");
+    assert!(patch(&crt25, 40..56).0 < patch(&crt22, 40..56).0 - 0.015, "gamma 2.5 should be darker");
+
+    // Channel noise added to linear light lands mostly in the shadows once encoded for display.
+    let shadow_noise = |config: &PipelineConfig| {
+        let mut config = config.clone();
+        config.channel.luma_noise = 0.02;
+        patch(&config, 8..24).1
+    };
+    let (gamma_noise, linear_noise) = (shadow_noise(&srgb), shadow_noise(&linear));
+    assert!(linear_noise > 3.0 * gamma_noise, "linear-light noise should swamp shadows: {linear_noise} {gamma_noise}");
+}
+
+#[test]
+fn legalizer_clips_saturated_colours_and_masks_them() {
+    let colours = [[1.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.9, 0.6, 0.45], [0.0, 0.0, 0.0]];
+    let mut frame = Frame::new(256, 16);
+    for (n, px) in frame.data.chunks_mut(3).enumerate() {
+        px.copy_from_slice(&colours[(n % 256) / 64]);
+    }
+    let mut config = quiet_config();
+    config.demodulation.filter = DemodulationFilter::AdaptiveComb;
+    config.composite.camera_transfer = CameraTransfer::Srgb;
+    config.receiver.display_transfer = DisplayTransfer::Srgb;
+    // Decoded Y'IQ at the centre of each patch; the output code values are R'G'B'.
+    let patches = |config: &PipelineConfig| {
+        let out = process_frame(&frame, config, 14_318_180.0);
+        (0..colours.len())
+            .map(|k| {
+                let idx = (8 * out.width + k * 64 + 40) * 3;
+                rgb_to_yiq(out.data[idx], out.data[idx + 1], out.data[idx + 2])
+            })
+            .collect::<Vec<Yiq>>()
+    };
+
+    let mask = illegal_mask(&frame, &config);
+    let flagged: Vec<bool> = (0..colours.len()).map(|k| mask[8 * 256 + k * 64 + 32]).collect();
+    assert_eq!(flagged, [true, true, false, false], "yellow tops 120 IRE, red dips below -20");
+    let mut overlay = config.clone();
+    overlay.debug.diagnostic_mode = true;
+    overlay.debug.show_illegal = true;
+    let painted = process_frame(&frame, &overlay, 14_318_180.0);
+    assert_eq!(&painted.data[(8 * 256 + 32) * 3..(8 * 256 + 33) * 3], &[1.0, 0.0, 1.0]);
+
+    config.composite.legalizer = LegalizerMode::Off;
+    let open = patches(&config);
+    config.composite.legalizer = LegalizerMode::Clip;
+    let clipped = patches(&config);
+    assert!(clipped[0].y < open[0].y - 0.005, "clipped yellow peaks should lose luma");
+    assert!((clipped[2].y - open[2].y).abs() < 0.002, "legal colours pass the clipper");
+
+    config.composite.legalizer = LegalizerMode::ReduceChroma;
+    let reduced = patches(&config);
+    let polar = |p: &Yiq| (p.i.hypot(p.q), p.q.atan2(p.i).to_degrees());
+    let ((open_amplitude, open_hue), (amplitude, hue)) = (polar(&open[0]), polar(&reduced[0]));
+    assert!((reduced[0].y - open[0].y).abs() < 0.003, "the legalizer keeps luma");
+    assert!(amplitude < 0.95 * open_amplitude && (hue - open_hue).abs() < 1.5, "and desaturates along the hue");
+
+    // A set aligned for NTSC-J (no setup) shows the 7.5 IRE pedestal as lifted black.
+    assert!(open[3].y.abs() < 0.01, "matched setup keeps black");
+    config.receiver.setup_ire = 0.0;
+    assert!(patches(&config)[3].y > 0.05, "setup mismatch should lift black");
+}
+
+#[test]
+fn capture_digitizer_quantizes_clips_jitters_and_subsamples() {
+    let mut base = quiet_config();
+    base.demodulation.filter = DemodulationFilter::AdaptiveComb;
+    base.composite.camera_transfer = CameraTransfer::Srgb;
+    base.receiver.display_transfer = DisplayTransfer::Srgb;
+    let capture = |setup: &dyn Fn(&mut PipelineConfig)| {
+        let mut config = base.clone();
+        config.capture.enabled = true;
+        setup(&mut config);
+        config
+    };
+
+    let mut ramp = Frame::new(128, 16);
+    for (n, px) in ramp.data.chunks_mut(3).enumerate() {
+        px.fill((n % 128) as f32 / 127.0);
+    }
+    let clean = luma_row(&process_frame(&ramp, &base, 14_318_180.0), 8);
+    let banding = |bits: u8| {
+        let row = luma_row(&process_frame(&ramp, &capture(&|c| c.capture.bits = bits), 14_318_180.0), 8);
+        let error: f32 = (8..120).map(|x| (row[x] - clean[x]).powi(2)).sum();
+        (error / 112.0).sqrt()
+    };
+    assert!(banding(3) > 0.03 && banding(10) < 0.001, "coarse ADCs should band the ramp");
+
+    let window = capture(&|c| {
+        c.capture.input_min_ire = 15.0;
+        c.capture.input_max_ire = 80.0;
+    });
+    let row = luma_row(&process_frame(&ramp, &window, 14_318_180.0), 8);
+    assert!(row[8] > 0.07 && row[120] < 0.8, "input window should clip blacks and whites");
+
+    let mut patch = Frame::new(64, 16);
+    for px in patch.data.chunks_mut(3) {
+        px.copy_from_slice(&[0.7, 0.3, 0.5]);
+    }
+    let decoded = |config: &PipelineConfig| {
+        let out = process_frame(&patch, config, 14_318_180.0);
+        (4..12)
+            .flat_map(|y| (16..48).map(move |x| (y * 64 + x) * 3))
+            .map(|idx| rgb_to_yiq(out.data[idx], out.data[idx + 1], out.data[idx + 2]))
+            .collect::<Vec<Yiq>>()
+    };
+    let i_spread = |jitter_ps: f32| {
+        let values = decoded(&capture(&|c| {
+            c.capture.bits = 12;
+            c.capture.clock_jitter_ps = jitter_ps;
+        }));
+        let mean = values.iter().map(|p| p.i).sum::<f32>() / values.len() as f32;
+        (values.iter().map(|p| (p.i - mean).powi(2)).sum::<f32>() / values.len() as f32).sqrt()
+    };
+    assert!(i_spread(10_000.0) > 1.4 * i_spread(0.0), "clock jitter should add chroma noise");
+
+    let mut sweep = Frame::new(64, 16);
+    for (n, px) in sweep.data.chunks_mut(3).enumerate() {
+        let t = (n % 64) as f32 / 63.0;
+        px.copy_from_slice(&[0.3 + 0.4 * t, 0.5, 0.7 - 0.4 * t]);
+    }
+    let dv = capture(&|c| c.capture.chroma_subsampling = ChromaSubsampling::Sub411);
+    let out = process_frame(&sweep, &dv, 14_318_180.0);
+    let i: Vec<f32> = (0..64)
+        .map(|x| {
+            let idx = (8 * 64 + x) * 3;
+            rgb_to_yiq(out.data[idx], out.data[idx + 1], out.data[idx + 2]).i
+        })
+        .collect();
+    for group in i[8..56].chunks(4) {
+        assert!(group.iter().all(|v| (v - group[0]).abs() < 1e-4), "4:1:1 holds chroma over 4 pixels");
+    }
+    assert!((i[12] - i[8]).abs() > 1e-3, "but follows the sweep between groups");
+}
+
+#[test]
+fn acc_follows_burst_and_colour_killer_switches_with_hysteresis() {
+    let mut frame = Frame::new(64, 64);
+    for px in frame.data.chunks_mut(3) {
+        px.copy_from_slice(&[0.8, 0.3, 0.3]);
+    }
+    // Red minus green at the centre of a row: zero once the killer drops the chroma.
+    let saturation = |out: &Frame, y: usize| {
+        let idx = (y * out.width + 32) * 3;
+        out.data[idx] - out.data[idx + 1]
+    };
+    let render = |burst_amplitude: f32, acc: bool| {
+        let mut config = quiet_config();
+        config.composite.burst_amplitude = burst_amplitude;
+        config.receiver.acc_enabled = acc;
+        saturation(&process_frame(&frame, &config, 14_318_180.0), 48)
+    };
+    let nominal = render(1.0, false);
+    assert!((render(0.6, false) - nominal).abs() < 0.005, "burst level alone leaves chroma alone");
+    assert!((render(1.0, true) - nominal).abs() < 0.005, "nominal burst means unity ACC gain");
+    assert!(render(0.6, true) > nominal + 0.1, "ACC should raise chroma when burst is weak");
+
+    let mut config = quiet_config();
+    config.receiver.color_killer_enabled = true;
+    let mut pipeline = Pipeline::new(config.clone(), 14_318_180.0);
+    let mut step = |burst_amplitude: f32| {
+        config.composite.burst_amplitude = burst_amplitude;
+        pipeline.set_config(config.clone());
+        pipeline.process(&frame);
+        saturation(&pipeline.process(&frame), 48)
+    };
+    assert!(step(0.2) > 0.1, "20% burst is above the 15% kill threshold");
+    assert!(step(0.1).abs() < 0.005, "a weak burst kills the colour");
+    assert!(step(0.2).abs() < 0.005, "hysteresis holds the killer until 25%");
+    assert!(step(0.3) > 0.1, "colour returns above the restore threshold");
+}
+
+#[test]
+fn raster_views_show_sync_blanking_burst_and_vbi() {
+    let mut frame = Frame::new(754, 60);
+    for px in frame.data.chunks_mut(3) {
+        px.copy_from_slice(&[0.8, 0.3, 0.3]);
+    }
+    let mut config = quiet_config();
+    config.vitc.enabled = true;
+    let active = process_frame(&frame, &config, 14_318_180.0);
+
+    config.debug.raster_view = RasterView::RawComposite;
+    let raw = process_frame(&frame, &config, 14_318_180.0);
+    assert_eq!((raw.width, raw.height), (910, 525));
+    let code = |row: usize, column: usize| (raw.data[(row * 910 + column) * 3] * 1023.0).round();
+    // Rows interleave the two fields: row 8 is line 5, a broad pulse; row 26 line 14 (VITC).
+    let broad = (0..910).filter(|&c| code(8, c) < 20.0).count();
+    assert!(broad > 750, "broad pulses hold sync level for most of the line ({broad})");
+    assert_eq!(code(300, 10), 16.0, "sync tip at 10-bit code 16");
+    assert_eq!(code(300, 72), 240.0, "blanking at 10-bit code 240");
+    let burst = (76..112).map(|c| code(300, c)).fold(0.0, f32::max);
+    assert!(burst > 320.0, "burst rides on the back porch ({burst})");
+    let vitc = (135..900).map(|c| code(26, c)).fold(0.0, f32::max);
+    assert!(vitc > 680.0, "VITC bits reach about 80 IRE ({vitc})");
+
+    // Other picture widths are resampled to the same line timing.
+    let mut wide = Frame::new(1280, 60);
+    for px in wide.data.chunks_mut(3) {
+        px.copy_from_slice(&[0.8, 0.3, 0.3]);
+    }
+    let wide_raw = process_frame(&wide, &config, 14_318_180.0);
+    let sync_width = |frame: &Frame| {
+        (0..910).filter(|&c| (frame.data[(300 * 910 + c) * 3] * 1023.0).round() < 20.0).count()
+    };
+    assert_eq!(sync_width(&wide_raw), sync_width(&raw), "4.7 us sync at any picture width");
+
+    config.debug.raster_view = RasterView::Underscan;
+    let underscan = process_frame(&frame, &config, 14_318_180.0);
+    assert_eq!((underscan.width, underscan.height), (910, 525));
+    assert!(luma_row(&underscan, 300)[10] < 0.01, "sync and blanking decode to black");
+    let mean = |row: &[f32]| row.iter().sum::<f32>() / row.len() as f32;
+    let picture = mean(&luma_row(&underscan, 300)[235..785]);
+    let expected = mean(&luma_row(&active, 32)[100..650]);
+    assert!((picture - expected).abs() < 0.05, "underscan picture {picture} vs {expected}");
+}
+
+#[test]
+fn presets_missing_newer_settings_load_with_defaults() {
+    let preset = r#"{
+        "composite": { "subcarrier_phase_deg": 10.0, "burst_amplitude": 1.0, "chroma_level": 0.8 },
+        "channel": { "luma_noise": 0.2 },
+        "demodulation": { "filter": "Notch" }
+    }"#;
+    let config: PipelineConfig = serde_json::from_str(preset).unwrap();
+    assert_eq!(config.composite.chroma_level, 0.8);
+    assert_eq!(config.channel.luma_noise, 0.2);
+    assert_eq!(config.demodulation.filter, DemodulationFilter::Notch);
+
+    let defaults = PipelineConfig::default();
+    assert_eq!(config.composite.signal_domain, defaults.composite.signal_domain);
+    assert_eq!(config.channel.luma_bandwidth_mhz, defaults.channel.luma_bandwidth_mhz);
+    assert_eq!(config.demodulation.axes, defaults.demodulation.axes);
+    assert_eq!(config.receiver.clamp, defaults.receiver.clamp);
+}
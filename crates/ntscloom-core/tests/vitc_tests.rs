@@ -0,0 +1,104 @@
+use ntscloom_core::vitc::{read_vitc, vitc_waveform, vitc_word};
+use ntscloom_core::{
+    CaptionTrack, Frame, Pipeline, PipelineConfig, Timecode, VbiConflict, VitcError,
+};
+
+#[test]
+fn drop_frame_timecode_skips_two_frames_a_minute_except_every_tenth() {
+    let tc = |index| Timecode::from_frame_index(index, true).to_string();
+    assert_eq!(tc(1799), "00:00:59;29");
+    assert_eq!(tc(1800), "00:01:00;02");
+    assert_eq!(tc(17981), "00:09:59;29");
+    assert_eq!(tc(17982), "00:10:00;00");
+    assert_eq!(Timecode::from_frame_index(1800, false).to_string(), "00:01:00:00");
+    for index in [0, 1799, 1800, 17982, 123_456] {
+        let timecode: Timecode = tc(index).parse().unwrap();
+        assert_eq!(timecode.frame_index(), index);
+    }
+    assert!("00:01:00;00".parse::<Timecode>().is_err(), "dropped frame number");
+    assert!("00:00:00:30".parse::<Timecode>().is_err());
+    assert!("00:00:00.02".parse::<Timecode>().is_err(), "only : and ; separate fields");
+}
+
+#[test]
+fn vitc_word_carries_sync_pairs_and_a_crc_that_catches_bit_errors() {
+    let timecode = Timecode::from_frame_index(54_321, true);
+    let word = vitc_word(timecode, 0x1234_5678);
+    assert_eq!(word.len(), 90);
+    for group in word.chunks(10) {
+        assert_eq!(&group[..2], &[true, false]);
+    }
+    let mut residues = [false; 8];
+    for (n, &bit) in word.iter().enumerate() {
+        residues[n % 8] ^= bit;
+    }
+    assert_eq!(residues, [false; 8], "x^8 + 1 divides the whole word");
+
+    let mut line = vitc_waveform(timecode, 0x1234_5678, 1500);
+    assert_eq!(read_vitc(&line), Ok(timecode));
+    // Invert data bit 20 (group 2, bit 4): sync is intact, so only the CRC can catch it.
+    let bit_len = 1500.0 / 95.3;
+    let start = ((3.3 + 20.0 + 4.0 + 0.3) * bit_len) as usize;
+    for level in &mut line[start..start + (0.4 * bit_len) as usize] {
+        *level = 0.8 - *level;
+    }
+    assert_eq!(read_vitc(&line), Err(VitcError::Crc));
+    assert_eq!(read_vitc(&vec![0.0; 1500]), Err(VitcError::NoSignal));
+}
+
+#[test]
+fn vitc_tracks_the_frame_index_through_the_channel_and_reports_failures() {
+    let frame = Frame::new(720, 24);
+    let mut config = PipelineConfig::default();
+    config.vitc.enabled = true;
+    let mut pipeline = Pipeline::new(config.clone(), 14_318_180.0);
+    pipeline.seek(17_980);
+    for index in 17_980..17_984 {
+        pipeline.process(&frame);
+        let expected = Timecode::from_frame_index(index, true);
+        let lines: Vec<u32> = pipeline.vitc().iter().map(|read| read.line).collect();
+        assert_eq!(lines, [14, 16]);
+        for read in pipeline.vitc() {
+            assert_eq!(read.result, Ok(expected), "line {}", read.line);
+        }
+    }
+    let report = pipeline.vitc_report();
+    assert_eq!((report.lines_read, report.lines_valid), (8, 8));
+
+    config.channel.luma_noise = 0.3;
+    let mut noisy = Pipeline::new(config, 14_318_180.0);
+    for _ in 0..8 {
+        noisy.process(&frame);
+    }
+    let report = noisy.vitc_report();
+    assert!(report.lines_valid < report.lines_read, "{report:?}");
+    assert!(report.crc_failures + report.sync_failures > 0, "{report:?}");
+}
+
+#[test]
+fn vbi_lines_claimed_twice_are_reported() {
+    let mut config = PipelineConfig::default();
+    config.vitc.enabled = true;
+    config.vits.enabled = true;
+    assert!(config.vbi_conflicts(true).is_empty(), "default lines are all distinct");
+
+    config.vitc.lines = vec![14, 21];
+    config.copy_protection.agc_pulses_enabled = true;
+    let conflict = |line, kept, dropped| VbiConflict { line, kept, dropped };
+    assert_eq!(
+        config.vbi_conflicts(false),
+        [
+            conflict(14, "VITC", "AGC pulses"),
+            conflict(17, "VITS", "AGC pulses"),
+            conflict(18, "VITS", "AGC pulses"),
+            conflict(19, "VITS", "AGC pulses"),
+        ]
+    );
+
+    config.copy_protection.agc_pulses_enabled = false;
+    let mut pipeline = Pipeline::new(config, 14_318_180.0);
+    assert!(pipeline.vbi_conflicts().is_empty());
+    pipeline.set_captions(Some(CaptionTrack::from_pairs(vec![[0x94, 0x20]])));
+    assert_eq!(pipeline.vbi_conflicts(), [conflict(21, "captions", "VITC")]);
+    assert_eq!(pipeline.vbi_conflicts()[0].to_string(), "VBI line 21: captions replaces VITC");
+}
@@ -0,0 +1,68 @@
+use ntscloom_core::{Frame, Pipeline, PipelineConfig, VitsMeasurement};
+
+fn quiet_config() -> PipelineConfig {
+    let mut config = PipelineConfig::default();
+    config.channel.luma_ringing = 0.0;
+    config.channel.luma_noise = 0.0;
+    config.tape.flutter_depth = 0.0;
+    config.tape.tracking_error = 0.0;
+    config.artifacts.head_switch_enabled = false;
+    config.artifacts.vertical_jitter_enabled = false;
+    config.artifacts.horizontal_tbc_enabled = false;
+    config.artifacts.chroma_phase_drift_enabled = false;
+    config.artifacts.dropout_enabled = false;
+    config.artifacts.saturation_enabled = false;
+    config.precision.pll_phase_noise = 0.0;
+    config.vits.enabled = true;
+    config
+}
+
+fn measure(config: PipelineConfig) -> VitsMeasurement {
+    let mut pipeline = Pipeline::new(config, 14_318_180.0);
+    pipeline.process(&Frame::new(720, 8));
+    pipeline.vits().clone()
+}
+
+#[test]
+fn vits_measure_frequency_response_dg_dp_and_k_factor_after_the_channel() {
+    // The default 4.2 MHz band limit is a smooth lowpass that already sags about 0.4 dB at
+    // 3.58 MHz; widen it so the clean channel is flat across the multiburst.
+    let mut config = quiet_config();
+    config.channel.luma_bandwidth_mhz = 6.0;
+    let clean = measure(config);
+    assert_eq!(clean.frequency_response.len(), 6);
+    for &(mhz, db) in &clean.frequency_response {
+        assert!(db.abs() < 0.1, "{mhz} MHz: {db} dB");
+    }
+    assert!(clean.differential_gain_pct.unwrap() < 0.1, "{clean:?}");
+    assert!(clean.differential_phase_deg.unwrap() < 0.1, "{clean:?}");
+    assert!(clean.k_pulse_bar_pct.unwrap() < 0.1, "{clean:?}");
+    assert!((clean.chroma_luma_gain_pct.unwrap() - 100.0).abs() < 2.0, "{clean:?}");
+
+    let mut config = quiet_config();
+    config.channel.differential_gain_pct_per_ire = 0.1;
+    config.channel.differential_phase_deg_per_ire = 0.1;
+    let nonlinear = measure(config);
+    assert!((nonlinear.differential_gain_pct.unwrap() - 10.0).abs() < 0.5, "{nonlinear:?}");
+    assert!((nonlinear.differential_phase_deg.unwrap() - 10.0).abs() < 0.5, "{nonlinear:?}");
+
+    let mut config = quiet_config();
+    config.channel.luma_bandwidth_mhz = 3.0;
+    config.channel.luma_ringing = 1.0;
+    config.channel.line_coupling_us = 200.0;
+    let band_limited = measure(config);
+    let response = &band_limited.frequency_response;
+    assert!(response[0].1.abs() < 0.5, "{response:?}");
+    assert!(response[5].1 < -10.0, "4.1 MHz is past the luma band: {response:?}");
+    assert!(band_limited.k_pulse_bar_pct.unwrap() > 0.5, "{band_limited:?}");
+    assert!(band_limited.bar_tilt_pct.unwrap() > 1.0, "{band_limited:?}");
+
+    // Without the dedicated lines the composite line still yields DG/DP and K.
+    let mut config = quiet_config();
+    config.vits.staircase_line = 0;
+    config.vits.pulse_bar_line = 0;
+    config.channel.differential_gain_pct_per_ire = 0.1;
+    let composite_only = measure(config);
+    assert!((composite_only.differential_gain_pct.unwrap() - 10.0).abs() < 0.5);
+    assert!(composite_only.k_pulse_bar_pct.is_some());
+}